@@ -0,0 +1,388 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::{context, format};
+use log::{Level, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+/// How often the background thread warns about records it had to drop.
+const REPORT_EVERY: Duration = Duration::from_secs(5);
+
+/// What to do with a record when the queue of the background thread is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Wait for space in the queue, so no logs are ever lost.
+    #[default]
+    Block,
+    /// Drop the new record, so logging never slows the program down.
+    DropNewest,
+    /// Drop the oldest record in the queue to make space for the new one,
+    /// so logging never slows the program down and the latest records,
+    /// which are usually the interesting ones, are kept.
+    DropOldest,
+}
+
+/// A record that owns everything, so it can be sent to another thread.
+struct OwnedRecord {
+    level: Level,
+    target: String,
+    message: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    thread: String,
+    context: Vec<(String, String)>,
+    stamp: format::Stamp,
+}
+
+enum Message {
+    Record(OwnedRecord),
+    Flush(SyncSender<()>),
+}
+
+/// The records waiting for the background thread. A channel can't drop the
+/// oldest message, so this is a queue behind a lock instead.
+struct Queue {
+    state: Mutex<State>,
+    changed: Condvar,
+    capacity: usize,
+    /// Records dropped since the last warning about them.
+    dropped: AtomicU64,
+}
+
+struct State {
+    messages: VecDeque<Message>,
+    closed: bool,
+}
+
+enum Next {
+    Message(Message),
+    Idle,
+    Closed,
+}
+
+impl Queue {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn push(&self, message: Message, overflow: Overflow) {
+        let mut state = self.lock();
+
+        // Flushes are never dropped, or whoever is flushing would wait forever.
+        let flushing = matches!(message, Message::Flush(_));
+        match overflow {
+            Overflow::Block => {
+                while state.messages.len() >= self.capacity && !state.closed {
+                    state = self.changed.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+            }
+            _ if flushing || state.messages.len() < self.capacity => {}
+            Overflow::DropNewest => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            Overflow::DropOldest => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+
+                let oldest = state.messages.iter().position(|message| matches!(message, Message::Record(_)));
+                match oldest {
+                    Some(oldest) => {
+                        state.messages.remove(oldest);
+                    }
+                    // The queue is all flushes, there's nothing to make space
+                    // with, so the new record is the one that's dropped.
+                    None => return,
+                }
+            }
+        }
+
+        if state.closed {
+            return;
+        }
+
+        state.messages.push_back(message);
+        self.changed.notify_all();
+    }
+
+    /// Wait up to `timeout` for the next message.
+    fn pop(&self, timeout: Duration) -> Next {
+        let mut state = self.lock();
+        if state.messages.is_empty() && !state.closed {
+            state = self
+                .changed
+                .wait_timeout(state, timeout)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+
+        match state.messages.pop_front() {
+            Some(message) => {
+                self.changed.notify_all();
+                Next::Message(message)
+            }
+            None if state.closed => Next::Closed,
+            None => Next::Idle,
+        }
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+        self.changed.notify_all();
+    }
+}
+
+/// Wraps a logger, handing every record to a background thread which
+/// does the formatting and writing.
+pub(crate) struct Background {
+    inner: Arc<dyn Log>,
+    queue: Arc<Queue>,
+    overflow: Overflow,
+    thread: Option<JoinHandle<()>>,
+    thread_id: ThreadId,
+}
+
+impl Background {
+    pub(crate) fn new(inner: Box<dyn Log>, capacity: usize, overflow: Overflow) -> std::io::Result<Self> {
+        let inner: Arc<dyn Log> = Arc::from(inner);
+        let queue = Arc::new(Queue {
+            state: Mutex::new(State {
+                messages: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            changed: Condvar::new(),
+            // Nothing could ever be queued with no space at all.
+            capacity: capacity.max(1),
+            dropped: AtomicU64::new(0),
+        });
+
+        let logger = Arc::clone(&inner);
+        let thread_queue = Arc::clone(&queue);
+        let thread = std::thread::Builder::new()
+            .name(String::from("sir_logger-async"))
+            .spawn(move || run(logger, thread_queue))?;
+
+        Ok(Self {
+            inner,
+            queue,
+            overflow,
+            thread_id: thread.thread().id(),
+            thread: Some(thread),
+        })
+    }
+
+    /// Records logged by the background thread itself, e.g. from the panic
+    /// hook, are written straight away, it would wait on itself otherwise.
+    fn on_background_thread(&self) -> bool {
+        std::thread::current().id() == self.thread_id
+    }
+}
+
+/// Write records until the logger is dropped.
+fn run(logger: Arc<dyn Log>, queue: Arc<Queue>) {
+    let mut reported = Instant::now();
+
+    loop {
+        match queue.pop(REPORT_EVERY) {
+            Next::Message(Message::Record(record)) => write(&*logger, record),
+            Next::Message(Message::Flush(done)) => {
+                report_dropped(&*logger, &queue);
+                logger.flush();
+                let _ = done.send(());
+            }
+            Next::Idle => {}
+            Next::Closed => break,
+        }
+
+        if reported.elapsed() >= REPORT_EVERY {
+            report_dropped(&*logger, &queue);
+            reported = Instant::now();
+        }
+    }
+
+    report_dropped(&*logger, &queue);
+    logger.flush();
+}
+
+fn write(logger: &dyn Log, record: OwnedRecord) {
+    // Format the record as if it was still on the thread
+    // that logged it, at the time it was logged.
+    let thread = record.thread.clone();
+    let context = record.context.clone();
+
+    format::logged_from(thread, || {
+        context::logged_with(context, || {
+            format::logged_at(record.stamp, || {
+                logger.log(
+                    &Record::builder()
+                        .level(record.level)
+                        .target(&record.target)
+                        .args(format_args!("{}", record.message))
+                        .module_path(record.module_path.as_deref())
+                        .file(record.file.as_deref())
+                        .line(record.line)
+                        .build(),
+                )
+            })
+        })
+    })
+}
+
+/// Warn about the records dropped since last time, if there were any.
+fn report_dropped(logger: &dyn Log, queue: &Queue) {
+    let dropped = queue.dropped.swap(0, Ordering::Relaxed);
+    if dropped == 0 {
+        return;
+    }
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Warn)
+            .target("sir_logger")
+            .args(format_args!("dropped {dropped} messages, the background queue was full"))
+            .module_path(Some(module_path!()))
+            .build(),
+    );
+}
+
+impl Log for Background {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        if self.on_background_thread() {
+            self.inner.log(record);
+            return;
+        }
+
+        let message = Message::Record(OwnedRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            module_path: record.module_path().map(str::to_string),
+            file: record.file().map(str::to_string),
+            line: record.line(),
+            thread: format::thread_name(),
+            context: context::fields(),
+            stamp: format::stamp(),
+        });
+
+        self.queue.push(message, self.overflow);
+    }
+
+    /// Wait for the background thread to write everything queued up so far.
+    fn flush(&self) {
+        if self.on_background_thread() {
+            self.inner.flush();
+            return;
+        }
+
+        let (done, wait) = mpsc::sync_channel(1);
+        self.queue.push(Message::Flush(done), self.overflow);
+        // Gives up straight away if the queue was closed, as `done` is dropped.
+        let _ = wait.recv();
+    }
+}
+
+impl Drop for Background {
+    /// Let the background thread finish off the queue before it stops.
+    fn drop(&mut self) {
+        self.queue.close();
+
+        if let Some(thread) = self.thread.take() {
+            if !self.on_background_thread() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(capacity: usize) -> Queue {
+        Queue {
+            state: Mutex::new(State {
+                messages: VecDeque::new(),
+                closed: false,
+            }),
+            changed: Condvar::new(),
+            capacity,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn record(message: &str) -> Message {
+        Message::Record(OwnedRecord {
+            level: Level::Info,
+            target: String::from("test"),
+            message: message.to_string(),
+            module_path: None,
+            file: None,
+            line: None,
+            thread: format::thread_name(),
+            context: Vec::new(),
+            stamp: format::stamp(),
+        })
+    }
+
+    #[test]
+    fn drop_oldest_drops_the_oldest_record() {
+        let queue = queue(2);
+        for message in ["first", "second", "third"] {
+            queue.push(record(message), Overflow::DropOldest);
+        }
+
+        let state = queue.lock();
+        let kept: Vec<&str> = state
+            .messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::Record(record) => Some(record.message.as_str()),
+                Message::Flush(_) => None,
+            })
+            .collect();
+        assert_eq!(kept, ["second", "third"]);
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn drop_oldest_counts_records_with_only_flushes_queued() {
+        let queue = queue(1);
+        let (done, _wait) = mpsc::sync_channel(1);
+        queue.push(Message::Flush(done), Overflow::DropOldest);
+        queue.push(record("dropped"), Overflow::DropOldest);
+
+        assert_eq!(queue.lock().messages.len(), 1);
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,1779 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::format::{self, ColorChoice, ControlChars, Layout, LevelColors, LevelPadding, LevelSymbols, MultiLine, OutputFormat, TargetLength, Timestamp, Timezone};
+#[cfg(feature = "async")]
+use crate::background::{Background, Overflow};
+use crate::context;
+use crate::dedup::Dedup;
+use crate::env;
+#[cfg(all(windows, feature = "eventlog"))]
+use crate::eventlog::EventLog;
+use crate::filter::{self, Filters, MetadataFilterFn, RecordFilter, RecordFilterFn, TargetLevel};
+use crate::file::{self, BufferedFile, FileMode, LogFile, Retention, Rotation, SizeRotatingFile, TimeRotatingFile};
+#[cfg(all(target_os = "linux", feature = "journald"))]
+use crate::journald::Journald;
+#[cfg(feature = "network")]
+use crate::network::{NetworkWriter, Transport};
+use crate::metrics;
+use crate::recent;
+#[cfg(feature = "tracing")]
+use crate::subscriber;
+use crate::switch::{self, LoggerGuard};
+use crate::{SetupError, PREVENT_MULTI_INIT};
+use log::LevelFilter;
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Write};
+#[cfg(feature = "network")]
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+#[cfg(all(feature = "panic-hook", not(feature = "no-panic-handler")))]
+use crate::panic_hook::{self, PanicOptions};
+
+/// A closure that formats a log line, see [`LoggerBuilder::format`].
+pub type FormatFn = dyn Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Send + Sync;
+
+/// A closure that is handed every record that passes the level filters.
+pub(crate) type RecordFn = dyn Fn(&log::Record) + Send + Sync;
+
+/// A shared closure (or writer), this only exists so the builder can
+/// still be `Clone` and `Debug`.
+pub(crate) struct Callback<T: ?Sized>(pub(crate) Arc<T>);
+
+impl<T: ?Sized> Clone for Callback<T> {
+    fn clone(&self) -> Self {
+        Callback(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Callback<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback(..)")
+    }
+}
+
+/// A writer added with [`LoggerBuilder::writer`], shared between
+/// the clones of the builder.
+type SharedWriter = Callback<Mutex<Box<dyn Write + Send>>>;
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).flush()
+    }
+}
+
+/// A builder for configuring the logger, this is a more readable
+/// alternative to [`setup`](fn@crate::setup) with a few extra options.
+///
+/// Only call [`init`](LoggerBuilder::init) **once**.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use log::LevelFilter;
+/// use sir_logger::LoggerBuilder;
+///
+/// LoggerBuilder::new()
+///     // The name of this executable, this'll help the library
+///     // set the correct log level for all crates.
+///     .root(env!("CARGO_PKG_NAME"))
+///     // Ignore `RUST_LOG` and log everything.
+///     .level(LevelFilter::Trace)
+///     .suppress(["very_verbose_crate"])
+///     .high_priority(["super_important_crate"])
+///     .log_file("path/to/log.txt")
+///     .init()
+///     .expect("Unable to setup logger");
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "the logger isn't installed until `init` is called"]
+pub struct LoggerBuilder {
+    level: Option<LevelFilter>,
+    suppress: Vec<Cow<'static, str>>,
+    high_priority: Vec<Cow<'static, str>>,
+    levels: Vec<(Cow<'static, str>, LevelFilter)>,
+    external_level: Option<LevelFilter>,
+    max_level: Option<LevelFilter>,
+    warn_if_initialized: bool,
+    log_files: Vec<LogFile>,
+    root: Option<Cow<'static, str>>,
+    color: Option<ColorChoice>,
+    timestamp: Timestamp,
+    timezone: Timezone,
+    source_location: bool,
+    thread: bool,
+    level_padding: LevelPadding,
+    target: bool,
+    target_length: TargetLength,
+    hash_target_colors: bool,
+    level_colors: LevelColors,
+    level_symbols: Option<LevelSymbols>,
+    full_line_color: bool,
+    hyperlinks: bool,
+    multi_line: MultiLine,
+    max_message_len: Option<usize>,
+    control_chars: ControlChars,
+    format: Option<Callback<FormatFn>>,
+    stdout_format: Option<OutputFormat>,
+    file_format: Option<OutputFormat>,
+    rotation: Rotation,
+    file_mode: FileMode,
+    file_buffer: usize,
+    retention: Retention,
+    #[cfg(feature = "gzip")]
+    compress: bool,
+    stderr: bool,
+    panic_hook: bool,
+    exit_on_panic: bool,
+    panic_exit_code: i32,
+    chain_panic_hook: bool,
+    structured_panics: bool,
+    panic_level: Option<log::Level>,
+    #[cfg(feature = "panic-webhook")]
+    panic_webhook: Option<String>,
+    stdout: bool,
+    stdout_level: LevelFilter,
+    file_level: LevelFilter,
+    ring_buffer: usize,
+    dedup: Option<Duration>,
+    context: Vec<(Cow<'static, str>, String)>,
+    #[cfg(feature = "async")]
+    background: Option<(usize, Overflow)>,
+    filter: Option<Callback<MetadataFilterFn>>,
+    filter_record: Option<Callback<RecordFilterFn>>,
+    #[cfg(all(unix, feature = "syslog"))]
+    syslog: Option<syslog::Facility>,
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    journald: bool,
+    #[cfg(all(windows, feature = "eventlog"))]
+    event_log: Option<Cow<'static, str>>,
+    #[cfg(feature = "network")]
+    network: Vec<(SocketAddr, Transport, OutputFormat)>,
+    writers: Vec<SharedWriter>,
+    on_record: Vec<Callback<RecordFn>>,
+    #[cfg(feature = "test-util")]
+    capture: Option<Callback<RecordFn>>,
+}
+
+impl Default for LoggerBuilder {
+    fn default() -> Self {
+        Self {
+            level: None,
+            suppress: Vec::new(),
+            high_priority: Vec::new(),
+            levels: Vec::new(),
+            external_level: None,
+            max_level: None,
+            warn_if_initialized: true,
+            log_files: Vec::new(),
+            root: None,
+            color: None,
+            timestamp: Timestamp::default(),
+            timezone: Timezone::default(),
+            source_location: false,
+            thread: false,
+            level_padding: LevelPadding::default(),
+            target: true,
+            target_length: TargetLength::default(),
+            hash_target_colors: false,
+            level_colors: LevelColors::default(),
+            level_symbols: None,
+            full_line_color: false,
+            hyperlinks: false,
+            multi_line: MultiLine::default(),
+            max_message_len: None,
+            control_chars: ControlChars::default(),
+            format: None,
+            stdout_format: None,
+            file_format: None,
+            rotation: Rotation::default(),
+            file_mode: FileMode::default(),
+            file_buffer: 0,
+            retention: Retention::default(),
+            #[cfg(feature = "gzip")]
+            compress: false,
+            stderr: false,
+            panic_hook: true,
+            exit_on_panic: false,
+            panic_exit_code: 1,
+            chain_panic_hook: false,
+            structured_panics: false,
+            panic_level: None,
+            #[cfg(feature = "panic-webhook")]
+            panic_webhook: None,
+            stdout: true,
+            stdout_level: LevelFilter::Trace,
+            file_level: LevelFilter::Trace,
+            ring_buffer: 0,
+            dedup: None,
+            context: Vec::new(),
+            #[cfg(feature = "async")]
+            background: None,
+            filter: None,
+            filter_record: None,
+            #[cfg(all(unix, feature = "syslog"))]
+            syslog: None,
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            journald: false,
+            #[cfg(all(windows, feature = "eventlog"))]
+            event_log: None,
+            #[cfg(feature = "network")]
+            network: Vec::new(),
+            writers: Vec::new(),
+            on_record: Vec::new(),
+            #[cfg(feature = "test-util")]
+            capture: None,
+        }
+    }
+}
+
+impl LoggerBuilder {
+    /// Create a builder with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder from the settings in a TOML config file, so the
+    /// logging can be changed without recompiling. Everything is optional,
+    /// and anything the file doesn't set can still be set on the builder.
+    ///
+    /// ```toml
+    /// level = "debug"            # like `level`, `RUST_LOG` is ignored when set
+    /// root = "my_app"
+    /// color = "auto"             # "auto", "always" or "never"
+    /// stdout = true
+    /// stdout_level = "info"
+    /// stdout_format = "text"     # "text", "json", "logfmt" or "gelf"
+    /// file = "logs/app.log"      # any number of files can go in `log_file`
+    /// file_level = "debug"
+    /// file_format = "json"
+    /// rotation = "daily"         # "never", "daily", "hourly" or
+    ///                            # { max_bytes = 10_000_000, keep = 5 }
+    ///
+    /// [levels]                   # like `level_for`
+    /// hyper = "warn"
+    /// "my_app::db" = "trace"
+    /// ```
+    ///
+    /// Unknown settings are an error, so typos don't go unnoticed.
+    ///
+    /// Requires the `config-file` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// sir_logger::LoggerBuilder::from_file("logging.toml")
+    ///     .expect("Unable to read the logging config")
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    #[cfg(feature = "config-file")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SetupError> {
+        let path = path.as_ref();
+        let invalid = |message| SetupError::Config {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let config = std::fs::read_to_string(path).map_err(|err| invalid(err.to_string()))?;
+        crate::config::apply(Self::new(), &config).map_err(invalid)
+    }
+
+    /// Override the log level, when set `RUST_LOG` will be ignored.
+    ///
+    /// If this isn't set, the level will be taken from `RUST_LOG`, and
+    /// then it'll default to `INFO`. `RUST_LOG` understands the same
+    /// syntax as `env_logger`, e.g. `RUST_LOG=info,hyper=warn,my_crate::db=trace`.
+    /// The targets in `RUST_LOG` take priority over everything else. Parts
+    /// of it that can't be understood, like `RUST_LOG=verbose`, are ignored
+    /// with a warning.
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// The names of crates that should be disabled for the logger.
+    ///
+    /// The names can be `&'static str`s or `String`s, so they can come
+    /// from a config file.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// let from_config = vec!["runtime_crate".to_string()];
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .suppress(from_config)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn suppress<T>(mut self, crates: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.suppress.extend(crates.into_iter().map(Into::into));
+        self
+    }
+
+    /// The names of crates that should be at the same log level as
+    /// the main program.
+    pub fn high_priority<T>(mut self, crates: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.high_priority.extend(crates.into_iter().map(Into::into));
+        self
+    }
+
+    /// The level for every crate that isn't the root, `high_priority`,
+    /// suppressed or given its own level.
+    ///
+    /// If this isn't set, external crates use the same level as the root,
+    /// so `RUST_LOG=debug` shows debug logs from dependencies too. Set this
+    /// to `warn` to only see warnings and errors from other crates.
+    pub fn external_level(mut self, level: LevelFilter) -> Self {
+        self.external_level = Some(level);
+        self
+    }
+
+    /// Never log anything more verbose than `level`, whatever the
+    /// [`level`](LoggerBuilder::level), `RUST_LOG`, the per-crate levels or
+    /// [`set_level`](crate::set_level) say. There's no cap by default.
+    ///
+    /// This is checked after all the other levels, so with a cap of `info`,
+    /// `RUST_LOG=trace` still shows `info`, `warn` and `error` but nothing
+    /// else, while `RUST_LOG=warn` stays at `warn`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use log::LevelFilter;
+    ///
+    /// // Stay quiet in release builds, even with `RUST_LOG=trace`.
+    /// let cap = if cfg!(debug_assertions) {
+    ///     LevelFilter::Trace
+    /// } else {
+    ///     LevelFilter::Info
+    /// };
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .max_level(cap)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn max_level(mut self, level: LevelFilter) -> Self {
+        self.max_level = Some(level);
+        self
+    }
+
+    /// Set the level of a single target, e.g. `hyper` or `my_crate::db`.
+    ///
+    /// These are applied after `suppress` and `high_priority`, so they take
+    /// priority over them. Just like `RUST_LOG`, the most specific target
+    /// wins, so `my_crate::db` overrides `my_crate`.
+    pub fn level_for(mut self, target: impl Into<Cow<'static, str>>, level: LevelFilter) -> Self {
+        self.levels.push((target.into(), level));
+        self
+    }
+
+    /// Set the levels of many targets at once, see
+    /// [`level_for`](LoggerBuilder::level_for).
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use log::LevelFilter;
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .levels([("hyper", LevelFilter::Warn), ("my_crate::db", LevelFilter::Trace)])
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn levels<T>(mut self, levels: impl IntoIterator<Item = (T, LevelFilter)>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        self.levels
+            .extend(levels.into_iter().map(|(target, level)| (target.into(), level)));
+        self
+    }
+
+    /// A path to a file to store logs in, the file is never colored. Any
+    /// missing directories in the path are created.
+    ///
+    /// The path can be a string literal, a `String` or a `PathBuf`. This can
+    /// be called more than once to write the same logs to several files.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use std::path::PathBuf;
+    ///
+    /// let dir = PathBuf::from("logs");
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .log_file("path/to/log.txt")
+    ///     .log_file(dir.join("app.txt"))
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn log_file(self, path: impl Into<PathBuf>) -> Self {
+        self.add_log_file(LogFile::new(path))
+    }
+
+    /// Add a file to store logs in, with its own settings. A file with a
+    /// [`level`](LogFile::level) gets a copy of just the records at that
+    /// level or above, e.g. to keep the errors somewhere easy to find.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use log::LevelFilter;
+    /// use sir_logger::{LogFile, LoggerBuilder};
+    ///
+    /// // Everything goes into `app.log`, but errors also go into `errors.log`.
+    /// LoggerBuilder::new()
+    ///     .log_file("logs/app.log")
+    ///     .add_log_file(LogFile::new("logs/errors.log").level(LevelFilter::Error))
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn add_log_file(mut self, file: LogFile) -> Self {
+        self.log_files.push(file);
+        self
+    }
+
+    /// The name of this executable, 9/10 times this should be the output
+    /// of `env!("CARGO_PKG_NAME")`.
+    pub fn root(mut self, root: impl Into<Cow<'static, str>>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// When to color the terminal output, defaults to [`ColorChoice::Auto`],
+    /// or `SIR_LOG_COLOR` when it's set.
+    ///
+    /// An explicit [`ColorChoice::Always`] or [`ColorChoice::Never`] takes
+    /// priority over both `NO_COLOR` and the terminal detection.
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// How the date of each log line is formatted, defaults
+    /// to [`Timestamp::Rfc3339Seconds`].
+    pub fn timestamp(mut self, timestamp: Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// The timezone that the date of each log line is shown in,
+    /// defaults to [`Timezone::Utc`].
+    pub fn timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Pad the level name to 5 characters so the messages line up,
+    /// defaults to [`LevelPadding::None`].
+    pub fn level_padding(mut self, padding: LevelPadding) -> Self {
+        self.level_padding = padding;
+        self
+    }
+
+    /// Change the color of each level name, the date and the target, see
+    /// [`LevelColors`] for the default palette.
+    pub fn level_colors(mut self, colors: LevelColors) -> Self {
+        self.level_colors = colors;
+        self
+    }
+
+    /// Put a symbol in front of each level name, e.g. `✖ ERROR` or
+    /// `⚠ WARN`, so they are easier to spot. Defaults to `false`.
+    ///
+    /// The symbols are colored like the level name, but the default ones
+    /// need a terminal that can show Unicode, see
+    /// [`custom_level_symbols`](LoggerBuilder::custom_level_symbols) to
+    /// pick others.
+    pub fn level_symbols(mut self, enable: bool) -> Self {
+        self.level_symbols = enable.then(LevelSymbols::default);
+        self
+    }
+
+    /// Put these symbols in front of the level names, this turns
+    /// on [`level_symbols`](LoggerBuilder::level_symbols).
+    pub fn custom_level_symbols(mut self, symbols: LevelSymbols) -> Self {
+        self.level_symbols = Some(symbols);
+        self
+    }
+
+    /// Color the whole line in the color of its level, instead of just the
+    /// level name. This makes errors really stand out, but the date and
+    /// target lose their own colors.
+    pub fn full_line_color(mut self, full_line: bool) -> Self {
+        self.full_line_color = full_line;
+        self
+    }
+
+    /// Give each target its own color, picked from a hash of its name, so
+    /// lines from the same module are easy to spot. By default every target
+    /// has the [`LevelColors::target`] color. This does nothing when the
+    /// output isn't colored.
+    pub fn hash_target_colors(mut self, hash: bool) -> Self {
+        self.hash_target_colors = hash;
+        self
+    }
+
+    /// Show the target of each line, e.g. `[date INFO my_app::db]`, defaults
+    /// to `true`. Turn it off for shorter lines like `[date INFO] message`.
+    /// This only changes [`OutputFormat::Text`].
+    pub fn target(mut self, show: bool) -> Self {
+        self.target = show;
+        self
+    }
+
+    /// Shorten long targets like `my_app::db::pool::conn`, defaults to
+    /// [`TargetLength::Full`]. This only changes [`OutputFormat::Text`].
+    pub fn target_length(mut self, length: TargetLength) -> Self {
+        self.target_length = length;
+        self
+    }
+
+    /// What to do with control characters in messages, defaults to
+    /// [`ControlChars::EscapeOutsideTerminal`] so nothing can hide in the
+    /// log files. Use [`ControlChars::Escape`] when logging untrusted input
+    /// to a terminal.
+    pub fn control_chars(mut self, control_chars: ControlChars) -> Self {
+        self.control_chars = control_chars;
+        self
+    }
+
+    /// Cut messages longer than `max` bytes short, ending them with
+    /// `…(truncated)`, so a stray huge message can't flood the outputs.
+    /// There's no limit by default.
+    pub fn max_message_len(mut self, max: usize) -> Self {
+        self.max_message_len = Some(max);
+        self
+    }
+
+    /// How messages with more than one line are written, defaults to
+    /// [`MultiLine::Raw`]. This only changes [`OutputFormat::Text`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sir_logger::{LoggerBuilder, MultiLine, Timestamp};
+    ///
+    /// LoggerBuilder::new()
+    ///     .timestamp(Timestamp::None)
+    ///     .multi_line(MultiLine::Indent)
+    ///     .ring_buffer(1)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    ///
+    /// log::info!(target: "app", "first\nsecond");
+    ///
+    /// assert_eq!(sir_logger::recent_logs(), ["[INFO app] first\n           second"]);
+    /// ```
+    pub fn multi_line(mut self, mode: MultiLine) -> Self {
+        self.multi_line = mode;
+        self
+    }
+
+    /// Show the file and line of the log call after the target, e.g.
+    /// `[date INFO my_app src/main.rs:12] message`. This is off by default
+    /// to keep lines short, and only changes [`OutputFormat::Text`].
+    pub fn source_location(mut self, show: bool) -> Self {
+        self.source_location = show;
+        self
+    }
+
+    /// Turn the `file:line` from [`source_location`](LoggerBuilder::source_location)
+    /// into a clickable link, using the OSC 8 escape code. Like colors, this
+    /// is only used for terminals and never written to log files. Off by
+    /// default, as some terminals print the escape codes as garbage.
+    pub fn hyperlinks(mut self, enable: bool) -> Self {
+        self.hyperlinks = enable;
+        self
+    }
+
+    /// Show the name of the thread that logged each line after the target,
+    /// e.g. `[date INFO my_app main] message`. Threads without a name show
+    /// their id instead, like `ThreadId(3)`. Like
+    /// [`source_location`](LoggerBuilder::source_location), this is off by
+    /// default and only changes [`OutputFormat::Text`].
+    pub fn thread(mut self, show: bool) -> Self {
+        self.thread = show;
+        self
+    }
+
+    /// Replace the default line layout with your own formatter, this is used
+    /// for both stdout and the log file.
+    ///
+    /// Custom formatters are responsible for their own coloring, so the
+    /// [`color`](LoggerBuilder::color), [`timestamp`](LoggerBuilder::timestamp)
+    /// and [`timezone`](LoggerBuilder::timezone) options are ignored.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// sir_logger::LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .format(|out, message, record| {
+    ///         out.finish(format_args!("{}: {message}", record.level()))
+    ///     })
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn format<F>(mut self, format: F) -> Self
+    where
+        F: Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Send + Sync + 'static,
+    {
+        self.format = Some(Callback(Arc::new(format)));
+        self
+    }
+
+    /// Write the logs to stdout (and stderr, see [`stderr`](LoggerBuilder::stderr)),
+    /// defaults to `true`. Turn this off when the logs are sent somewhere
+    /// else, like a log file or journald.
+    pub fn stdout(mut self, enable: bool) -> Self {
+        self.stdout = enable;
+        self
+    }
+
+    /// Only write records at `level` or above to stdout (and stderr), the
+    /// log files and other outputs still get everything else. Like
+    /// [`LogFile::level`], this can't let through anything that the
+    /// [`level`](LoggerBuilder::level) drops.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use log::LevelFilter;
+    /// use sir_logger::{LogFile, LoggerBuilder, OutputFormat};
+    ///
+    /// // Colored text at `debug` in the terminal, and JSON at `info` in the file.
+    /// LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .level(LevelFilter::Debug)
+    ///     .stdout_level(LevelFilter::Debug)
+    ///     .add_log_file(
+    ///         LogFile::new("path/to/log.json")
+    ///             .level(LevelFilter::Info)
+    ///             .format(OutputFormat::Json),
+    ///     )
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn stdout_level(mut self, level: LevelFilter) -> Self {
+        self.stdout_level = level;
+        self
+    }
+
+    /// Only write records at `level` or above to the log files, the other
+    /// way around from [`stdout_level`](LoggerBuilder::stdout_level). A
+    /// file with its own [`LogFile::level`] gets whichever is less verbose.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use log::LevelFilter;
+    /// use sir_logger::LoggerBuilder;
+    ///
+    /// // Everything down to `debug` in the file, but just `info` in the terminal.
+    /// LoggerBuilder::new()
+    ///     .level(LevelFilter::Debug)
+    ///     .stdout_level(LevelFilter::Info)
+    ///     .file_level(LevelFilter::Debug)
+    ///     .log_file("app.log")
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn file_level(mut self, level: LevelFilter) -> Self {
+        self.file_level = level;
+        self
+    }
+
+    /// Send warnings and errors to stderr instead of stdout, so they can be
+    /// redirected separately. Defaults to `false`, where everything goes
+    /// to stdout.
+    ///
+    /// Coloring is decided separately for stdout and stderr, and both use
+    /// the [`stdout_format`](LoggerBuilder::stdout_format).
+    pub fn stderr(mut self, split: bool) -> Self {
+        self.stderr = split;
+        self
+    }
+
+    /// The layout of the lines written to stdout, defaults
+    /// to [`OutputFormat::Text`], or `SIR_LOG_FORMAT` when it's set.
+    pub fn stdout_format(mut self, format: OutputFormat) -> Self {
+        self.stdout_format = Some(format);
+        self
+    }
+
+    /// The layout of the lines written to the log files, defaults
+    /// to [`OutputFormat::Text`], or `SIR_LOG_FORMAT` when it's set. Single
+    /// files can override this
+    /// with [`LogFile::format`].
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use sir_logger::{LoggerBuilder, OutputFormat};
+    ///
+    /// // Pretty text in the terminal, but JSON in the file.
+    /// LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .log_file("path/to/log.json")
+    ///     .file_format(OutputFormat::Json)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn file_format(mut self, format: OutputFormat) -> Self {
+        self.file_format = Some(format);
+        self
+    }
+
+    /// When the log files should be rotated, defaults to [`Rotation::Never`].
+    ///
+    /// Daily and hourly rotation use the [`timezone`](LoggerBuilder::timezone)
+    /// to decide when a day or hour starts.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use sir_logger::{LoggerBuilder, Rotation};
+    ///
+    /// // Keep at most 5 old files of 10 MiB each.
+    /// LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .log_file("path/to/log.txt")
+    ///     .rotation(Rotation::Size { max_bytes: 10 * 1024 * 1024, keep: 5 })
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// How the log files are opened, defaults to [`FileMode::Append`] so
+    /// restarting doesn't wipe the old logs.
+    pub fn file_mode(mut self, mode: FileMode) -> Self {
+        self.file_mode = mode;
+        self
+    }
+
+    /// Compress rotated log files with gzip, e.g. to `log.txt.1.gz`, on a
+    /// background thread. The `keep` count of [`Rotation::Size`] includes
+    /// the compressed files. Failures are logged as warnings, and the
+    /// uncompressed file is left alone. Does nothing without [`rotation`](LoggerBuilder::rotation).
+    ///
+    /// Requires the `gzip` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use sir_logger::{LoggerBuilder, Rotation};
+    ///
+    /// LoggerBuilder::new()
+    ///     .log_file("app.log")
+    ///     .rotation(Rotation::Size { max_bytes: 10_000_000, keep: 5 })
+    ///     .compress(true)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Delete old rotated log files by age or total size, see [`Retention`].
+    /// Does nothing without [`rotation`](LoggerBuilder::rotation).
+    pub fn retention(mut self, retention: Retention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Hold up to `capacity` bytes of lines in memory before writing them to
+    /// the log files, which is much faster than a write for every line when
+    /// logging a lot. Defaults to `0`, where every line is written straight
+    /// away.
+    ///
+    /// The lines are written at least every second, and when
+    /// [`flush`](crate::flush) is called or the program panics. Call `flush`
+    /// before exiting, or the last second of logs might be lost.
+    ///
+    /// With [`Rotation::Size`] the file is only checked once per write, so
+    /// it can go past `max_bytes` by up to `capacity` bytes before it is
+    /// rotated. Records are still never split between two files.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// sir_logger::LoggerBuilder::new()
+    ///     .log_file("app.log")
+    ///     .file_buffer(64 * 1024)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    ///
+    /// log::info!("started");
+    /// sir_logger::flush();
+    /// ```
+    pub fn file_buffer(mut self, capacity: usize) -> Self {
+        self.file_buffer = capacity;
+        self
+    }
+
+    /// Install a panic hook that logs panics, defaults to `true`. When the
+    /// level is [`LevelFilter::Off`] the panics are handed to the hook that
+    /// was there before instead, so they don't go missing.
+    ///
+    /// Turn this off in libraries, tests or apps that already have their
+    /// own panic hook. To leave the hook out of the build entirely, turn
+    /// off the default `panic-hook` feature, this does nothing then.
+    pub fn panic_hook(mut self, install: bool) -> Self {
+        self.panic_hook = install;
+        self
+    }
+
+    /// Exit the process straight after a panic has been logged, defaults
+    /// to `false`.
+    ///
+    /// When this is off the panic carries on unwinding as normal, so
+    /// `catch_unwind`, test harnesses and destructors all still work.
+    /// When it's on the process exits immediately without unwinding.
+    pub fn exit_on_panic(mut self, exit: bool) -> Self {
+        self.exit_on_panic = exit;
+        self
+    }
+
+    /// The exit code used when [`exit_on_panic`](LoggerBuilder::exit_on_panic)
+    /// is on, defaults to `1`.
+    pub fn panic_exit_code(mut self, code: i32) -> Self {
+        self.panic_exit_code = code;
+        self
+    }
+
+    /// Also run the panic hook that was installed before the logger's,
+    /// defaults to `false`. Turn this on when another library (or your own
+    /// code) has a hook that still needs to see the panics.
+    ///
+    /// The previous hook runs after the panic has been logged and flushed,
+    /// and before exiting when [`exit_on_panic`](LoggerBuilder::exit_on_panic)
+    /// is on. When no other hook was installed this is the default one from
+    /// `std`, which prints the panic to stderr a second time.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// static RAN: AtomicBool = AtomicBool::new(false);
+    ///
+    /// std::panic::set_hook(Box::new(|_| RAN.store(true, Ordering::SeqCst)));
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .chain_panic_hook(true)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    ///
+    /// let _ = std::panic::catch_unwind(|| panic!("oh no"));
+    /// assert!(RAN.load(Ordering::SeqCst));
+    /// ```
+    pub fn chain_panic_hook(mut self, chain: bool) -> Self {
+        self.chain_panic_hook = chain;
+        self
+    }
+
+    /// Log everything about a panic at `level`. By default the message (and
+    /// backtrace) are logged at `error`, with where it happened at `debug`.
+    ///
+    /// If `level` is filtered out the panic is handed to the hook that was
+    /// there before, just like when logging is off.
+    pub fn panic_level(mut self, level: log::Level) -> Self {
+        self.panic_level = Some(level);
+        self
+    }
+
+    /// Log each panic as a single record instead of a few lines, with
+    /// the details in `panic.message`, `panic.file`, `panic.line` and (when
+    /// `RUST_BACKTRACE` is set) `panic.backtrace` fields. Defaults to `false`.
+    ///
+    /// This works best with [`OutputFormat::Json`] or [`OutputFormat::Logfmt`],
+    /// where each field gets its own key, so crashes can be picked out of the
+    /// logs by a machine.
+    pub fn structured_panics(mut self, structured: bool) -> Self {
+        self.structured_panics = structured;
+        self
+    }
+
+    /// POST every panic to `url` as JSON, e.g. a Slack or ops webhook. The
+    /// body has the `message`, `file`, `line` and (when `RUST_BACKTRACE` is
+    /// set) the `backtrace`, and a `text` summary for Slack.
+    ///
+    /// This is best-effort, the panic is logged before it's sent and if the
+    /// request fails (or takes longer than 5 seconds) there's just a warning
+    /// in the logs. Does nothing without the [`panic_hook`](LoggerBuilder::panic_hook).
+    ///
+    /// Requires the `panic-webhook` feature.
+    #[cfg(feature = "panic-webhook")]
+    pub fn panic_webhook(mut self, url: impl Into<String>) -> Self {
+        self.panic_webhook = Some(url.into());
+        self
+    }
+
+    /// Keep the last `capacity` lines in memory, so they can be read back
+    /// with [`recent_logs`](crate::recent_logs). Defaults to `0`, which
+    /// keeps nothing.
+    pub fn ring_buffer(mut self, capacity: usize) -> Self {
+        self.ring_buffer = capacity;
+        self
+    }
+
+    /// Also send the logs to the local syslog daemon, using `facility`,
+    /// e.g. [`Facility::LOG_DAEMON`](crate::Facility::LOG_DAEMON). Each level
+    /// is sent with the matching syslog severity, `Debug` and `Trace` are
+    /// both sent as `LOG_DEBUG`.
+    ///
+    /// Requires the `syslog` feature, and only works on unix.
+    #[cfg(all(unix, feature = "syslog"))]
+    pub fn syslog(mut self, facility: syslog::Facility) -> Self {
+        self.syslog = Some(facility);
+        self
+    }
+
+    /// Also send the logs to journald using its native protocol, so the
+    /// level, target and source location are kept as their own fields and
+    /// `journalctl -p` works. The message is sent as is, without the date
+    /// or level in front.
+    ///
+    /// Under systemd, stdout usually ends up in the journal as well, so use
+    /// [`stdout(false)`](LoggerBuilder::stdout) to avoid every line showing
+    /// up twice.
+    ///
+    /// Requires the `journald` feature, and only works on linux.
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    pub fn journald(mut self, enable: bool) -> Self {
+        self.journald = enable;
+        self
+    }
+
+    /// Also write the logs to the Windows Event Log, under the event
+    /// source called `source`. Errors and warnings keep their type, every
+    /// other level is written as information.
+    ///
+    /// The source should be registered when installing your program, e.g.
+    /// with `New-EventLog`, otherwise the Event Viewer complains that the
+    /// description of each event can't be found, but still shows the message.
+    ///
+    /// Requires the `eventlog` feature, and only works on windows.
+    #[cfg(all(windows, feature = "eventlog"))]
+    pub fn event_log(mut self, source: impl Into<Cow<'static, str>>) -> Self {
+        self.event_log = Some(source.into());
+        self
+    }
+
+    /// Also send every line to a remote collector at `addr`, this can be
+    /// called more than once to send to several collectors. The lines use
+    /// the plain [`OutputFormat::Text`] layout without colors.
+    ///
+    /// TCP connects once when the logger is installed, and `init` fails if
+    /// the collector can't be reached. After that, a broken connection is
+    /// retried on the next line, and if that fails it isn't tried again for
+    /// 5 seconds. Nothing is buffered, a line that can't be sent within a
+    /// second is dropped, so a slow collector only slows down logging for
+    /// a moment.
+    ///
+    /// Requires the `network` feature.
+    #[cfg(feature = "network")]
+    pub fn network(mut self, addr: SocketAddr, transport: Transport) -> Self {
+        self.network.push((addr, transport, OutputFormat::Text));
+        self
+    }
+
+    /// Send every record to a Graylog server at `addr`, as GELF. Over TCP
+    /// the messages are ended with a nul byte, as Graylog expects. This
+    /// works just like [`network`](LoggerBuilder::network) otherwise.
+    ///
+    /// Requires the `network` feature.
+    #[cfg(feature = "network")]
+    pub fn gelf(mut self, addr: SocketAddr, transport: Transport) -> Self {
+        self.network.push((addr, transport, OutputFormat::Gelf));
+        self
+    }
+
+    /// Also write every line to `writer`, e.g. a socket that is already
+    /// open or a buffer in memory. This can be called more than once. The
+    /// lines use the [`file_format`](LoggerBuilder::file_format) layout
+    /// without colors.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use std::io::{self, Write};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Shared(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Shared {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let buffer = Shared::default();
+    ///
+    /// let guard = sir_logger::LoggerBuilder::new()
+    ///     .stdout(false)
+    ///     .writer(buffer.clone())
+    ///     .init_scoped()
+    ///     .expect("Unable to setup logger");
+    ///
+    /// log::info!("into the buffer");
+    /// drop(guard);
+    ///
+    /// let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    /// assert!(logs.contains("into the buffer"));
+    /// ```
+    pub fn writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        self.writers.push(Callback(Arc::new(Mutex::new(writer))));
+        self
+    }
+
+    /// Call `callback` with every record that gets past the filters, on top
+    /// of writing it out as usual. This is handy for side effects like
+    /// bumping a metric or firing an alert on errors, and can be called
+    /// more than once.
+    ///
+    /// The callback runs in the thread that logged the record (or the
+    /// background thread, with the `async` feature),
+    /// so it has to be `Send + Sync` and should return quickly, anything
+    /// slow stalls the logging. Logging from inside the callback isn't
+    /// a good idea either.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    ///
+    /// static ERRORS: AtomicU64 = AtomicU64::new(0);
+    ///
+    /// sir_logger::LoggerBuilder::new()
+    ///     .on_record(|record| {
+    ///         if record.level() == log::Level::Error {
+    ///             ERRORS.fetch_add(1, Ordering::Relaxed);
+    ///         }
+    ///     })
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn on_record<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&log::Record) + Send + Sync + 'static,
+    {
+        self.on_record.push(Callback(Arc::new(callback)));
+        self
+    }
+
+    /// Only log the records that `filter` returns `true` for, e.g. to drop
+    /// everything from a single module. This runs as well as the levels,
+    /// it can't let through anything that they would drop.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// sir_logger::LoggerBuilder::new()
+    ///     .filter(|metadata| !metadata.target().ends_with("::noisy"))
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&log::Metadata) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Callback(Arc::new(filter)));
+        self
+    }
+
+    /// Like [`filter`](LoggerBuilder::filter), but with the whole record,
+    /// so records can be dropped based on the message. The message has to
+    /// be formatted to check it, so this is slower than `filter`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// sir_logger::LoggerBuilder::new()
+    ///     .filter_record(|record| !record.args().to_string().contains("password"))
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn filter_record<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&log::Record) -> bool + Send + Sync + 'static,
+    {
+        self.filter_record = Some(Callback(Arc::new(filter)));
+        self
+    }
+
+    /// Add a field to every line, like calling [`set_context`](crate::set_context)
+    /// once the logger is installed.
+    pub fn context(mut self, key: impl Into<Cow<'static, str>>, value: impl ToString) -> Self {
+        self.context.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Drop lines that are exactly the same as the line before, with the
+    /// same level and target. Once a different line is logged, or `window`
+    /// has passed, a `... (repeated N times)` line is logged instead of
+    /// all the copies. The summary is logged by a background thread once
+    /// the window is over, even if nothing else is logged. Off by default.
+    ///
+    /// The check is done before the outputs, so it applies to all of them.
+    pub fn dedup(mut self, window: Duration) -> Self {
+        self.dedup = Some(window);
+        self
+    }
+
+    /// Format and write the logs on a background thread, so slow outputs
+    /// like files on a busy disk or the network don't hold up the thread
+    /// that is logging. Up to `capacity` records are queued, `overflow`
+    /// decides what happens when the queue is full.
+    ///
+    /// [`flush`](crate::flush) waits for the queue to be written. The queue
+    /// is only written out on exit when `flush` is called, or the logger is
+    /// reset, e.g. by dropping the guard from
+    /// [`init_scoped`](LoggerBuilder::init_scoped).
+    ///
+    /// When records are dropped, a `dropped N messages` warning is logged
+    /// at most every few seconds, and on flush.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use std::io::{self, Write};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    /// use sir_logger::{LoggerBuilder, Overflow};
+    ///
+    /// // A writer slow enough for the queue to fill up.
+    /// #[derive(Clone, Default)]
+    /// struct Slow(Arc<Mutex<Vec<u8>>>);
+    ///
+    /// impl Write for Slow {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         std::thread::sleep(Duration::from_millis(2));
+    ///         self.0.lock().unwrap().write(buf)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let burst = |overflow| {
+    ///     let slow = Slow::default();
+    ///     let guard = LoggerBuilder::new()
+    ///         .stdout(false)
+    ///         .writer(slow.clone())
+    ///         .background(4, overflow)
+    ///         .init_scoped()
+    ///         .expect("Unable to setup logger");
+    ///
+    ///     for i in 0..100 {
+    ///         log::info!("message {i}");
+    ///     }
+    ///     sir_logger::flush();
+    ///     drop(guard);
+    ///
+    ///     let logs = String::from_utf8(slow.0.lock().unwrap().clone()).unwrap();
+    ///     logs
+    /// };
+    ///
+    /// let logs = burst(Overflow::Block);
+    /// assert_eq!(logs.lines().count(), 100);
+    /// assert!(!logs.contains("dropped"));
+    ///
+    /// let logs = burst(Overflow::DropNewest);
+    /// assert!(logs.contains("message 0\n") && !logs.contains("message 99\n"));
+    /// assert!(logs.contains("dropped"));
+    ///
+    /// let logs = burst(Overflow::DropOldest);
+    /// assert!(!logs.contains("message 50\n") && logs.contains("message 99\n"));
+    /// assert!(logs.contains("dropped"));
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn background(mut self, capacity: usize, overflow: Overflow) -> Self {
+        self.background = Some((capacity, overflow));
+        self
+    }
+
+    /// Install the logger.
+    ///
+    /// Returns a [`SetupError`] if the logger was already initialized, the
+    /// log file couldn't be opened or another logger is already installed.
+    /// It is safe to race calls to `init` from multiple threads, only one
+    /// of them will succeed.
+    ///
+    /// ## Environment variables
+    ///
+    /// On top of `RUST_LOG`, these fill in whatever wasn't set on the
+    /// builder (or in the config file of [`from_file`](LoggerBuilder::from_file)),
+    /// so a deployment can change them without recompiling:
+    ///
+    /// - `SIR_LOG_FILE`, a log file to write to, when no log files were added.
+    /// - `SIR_LOG_COLOR`, `auto`, `always` or `never`, see [`color`](LoggerBuilder::color).
+    /// - `SIR_LOG_FORMAT`, `text`, `json`, `logfmt` or `gelf`, for both
+    ///   [`stdout_format`](LoggerBuilder::stdout_format) and
+    ///   [`file_format`](LoggerBuilder::file_format).
+    ///
+    /// Empty ones are ignored, and ones that can't be parsed are ignored with
+    /// a warning.
+    ///
+    /// ## Example
+    ///
+    /// ```rust,no_run
+    /// // Run with e.g. `SIR_LOG_FILE=app.log SIR_LOG_FORMAT=json`, the
+    /// // level is kept at `info` either way.
+    /// sir_logger::LoggerBuilder::new()
+    ///     .level(log::LevelFilter::Info)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    /// ```
+    pub fn init(self) -> Result<(), SetupError> {
+        // Claim the flag up-front so two threads can't both get past here.
+        if PREVENT_MULTI_INIT
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            if self.warn_if_initialized {
+                log::warn!("Attempted to initialize logger twice, ensure you call `setup` once.");
+            }
+
+            return Err(SetupError::AlreadyInitialized);
+        }
+
+        // A failed `install` hasn't changed any of the shared state, so
+        // nothing was setup and the flag can be given back. Otherwise a
+        // bad log file would stop `init` from ever being retried, e.g. with
+        // a different path.
+        let result = self.install();
+        if result.is_err() {
+            PREVENT_MULTI_INIT.store(false, Ordering::Release);
+        }
+
+        result
+    }
+
+    /// Log a warning when the logger has already been setup, defaults to
+    /// `true`. [`init`](LoggerBuilder::init) still returns
+    /// [`SetupError::AlreadyInitialized`] either way.
+    ///
+    /// Libraries that setup logging just in case should turn this off, or
+    /// check [`is_initialized`](crate::is_initialized) first.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sir_logger::{LoggerBuilder, SetupError};
+    ///
+    /// sir_logger::init_default("my_app").expect("Unable to setup logger");
+    ///
+    /// // In a library, happy to use whatever the app already setup.
+    /// match LoggerBuilder::new().warn_if_initialized(false).init() {
+    ///     Ok(()) | Err(SetupError::AlreadyInitialized) => {}
+    ///     Err(err) => panic!("Unable to setup logger: {err}"),
+    /// }
+    /// ```
+    pub fn warn_if_initialized(mut self, warn: bool) -> Self {
+        self.warn_if_initialized = warn;
+        self
+    }
+
+    /// Check everything that could make [`init`](LoggerBuilder::init) fail,
+    /// without installing the logger, so a bad config can be reported early.
+    /// This gives the same errors as `init` would.
+    ///
+    /// The logger can't be setup yet, and the log files have to be openable,
+    /// including `SIR_LOG_FILE` (see [`init`](LoggerBuilder::init)).
+    /// Empty crate names are caught too, `init` just ignores them as they
+    /// can't match anything. The files (and their directories) aren't
+    /// created, just a file that is removed straight away to check the
+    /// directory can be written to. Connections to syslog, journald or
+    /// collectors aren't tried, so `init` can still fail on those.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// let builder = sir_logger::LoggerBuilder::new()
+    ///     .root(env!("CARGO_PKG_NAME"))
+    ///     .log_file(std::env::temp_dir());
+    ///
+    /// // A directory isn't a log file.
+    /// assert!(builder.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), SetupError> {
+        if crate::is_initialized() {
+            return Err(SetupError::AlreadyInitialized);
+        }
+
+        let targets = self
+            .root
+            .iter()
+            .chain(&self.suppress)
+            .chain(&self.high_priority)
+            .chain(self.levels.iter().map(|(target, _)| target));
+
+        for target in targets {
+            if target.trim().is_empty() {
+                return Err(SetupError::EmptyTarget);
+            }
+        }
+
+        let from_env = env::overrides().file.filter(|_| self.log_files.is_empty());
+        let paths = self.log_files.iter().map(|log_file| log_file.path.clone()).chain(from_env);
+        for path in paths {
+            file::check(&path).map_err(|source| SetupError::LogFile { path, source })?;
+        }
+
+        Ok(())
+    }
+
+    /// Install the logger, and reset it again once the returned guard is
+    /// dropped so that it can be setup again. This is mostly useful in tests.
+    ///
+    /// The `log` crate doesn't allow removing a logger, so once the guard
+    /// is dropped everything is silently thrown away until the logger is
+    /// setup again. Resetting also throws away the [`recent_logs`](crate::recent_logs)
+    /// and the fields from [`set_context`](crate::set_context).
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sir_logger::LoggerBuilder;
+    ///
+    /// for _ in 0..2 {
+    ///     let _guard = LoggerBuilder::new()
+    ///         .root(env!("CARGO_PKG_NAME"))
+    ///         .init_scoped()
+    ///         .expect("Unable to setup logger");
+    ///
+    ///     log::info!("logged twice, once per logger");
+    /// }
+    /// ```
+    pub fn init_scoped(self) -> Result<LoggerGuard, SetupError> {
+        self.init().map(|()| LoggerGuard::new())
+    }
+
+    /// Install the logger, but capture every record in memory instead of
+    /// printing it to stdout. Log files are still written to. Requires
+    /// the `test-util` feature, see [`capture`](crate::capture).
+    #[cfg(feature = "test-util")]
+    pub fn capture(mut self) -> Result<crate::Capture, SetupError> {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&records);
+
+        self.stdout = false;
+        self.capture = Some(Callback(Arc::new(move |record: &log::Record| {
+            sink.lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .push((record.level(), record.target().to_string(), record.args().to_string()));
+        })));
+
+        crate::Capture::start(self, records)
+    }
+
+    /// Build and switch in the logger. Everything that can fail is done
+    /// first, so a failed `init` doesn't change anything.
+    fn install(mut self) -> Result<(), SetupError> {
+        // The `SIR_LOG_*` variables only fill in what the builder left unset.
+        let overrides = env::overrides();
+        let color = self.color.or(overrides.color).unwrap_or_default();
+        let stdout_format = self.stdout_format.or(overrides.format).unwrap_or_default();
+        let file_format = self.file_format.or(overrides.format).unwrap_or_default();
+        if let (true, Some(path)) = (self.log_files.is_empty(), overrides.file) {
+            self.log_files.push(LogFile::new(path));
+        }
+
+        // Check if log level is overridden, if not, attempt to look
+        // for the environment variable and fallback to `Info`
+        let directives = match self.level {
+            Some(_) => env::Directives::default(),
+            None => env::rust_log(),
+        };
+
+        let level = self
+            .level
+            .or(directives.level)
+            .unwrap_or(LevelFilter::Info);
+
+        // Setup the colors of each level, this'll only be used when
+        // printing the name of the log level e.g. "INFO".
+        let layout = Layout {
+            colors_level: self.level_colors.into(),
+            date_color: self.level_colors.date,
+            target_color: self.level_colors.target,
+            timestamp: self.timestamp,
+            timezone: self.timezone,
+            source_location: self.source_location,
+            thread: self.thread,
+            level_padding: self.level_padding,
+            level_symbols: self.level_symbols,
+            target: self.target,
+            target_length: self.target_length,
+            hash_target_colors: self.hash_target_colors,
+            full_line_color: self.full_line_color,
+            hyperlinks: self.hyperlinks,
+            multi_line: self.multi_line,
+            max_message_len: self.max_message_len,
+            escape_control: self.control_chars == ControlChars::Escape,
+        };
+
+        // Everything that isn't a terminal.
+        let file_layout = Layout {
+            escape_control: self.control_chars != ControlChars::Keep,
+            ..layout
+        };
+
+        // Setup the default logging levels for all crates.
+        let mut filters = Filters::new(level, self.external_level);
+
+        // Override the main crate to have different
+        // log levels.
+        if let Some(root) = &self.root {
+            filters.set(root.clone(), TargetLevel::Main);
+        }
+
+        filters.set(crate::RAW_TARGET.into(), TargetLevel::Main);
+
+        // Apply all the overrides.
+        for pkg in &self.high_priority {
+            filters.set(pkg.clone(), TargetLevel::Main);
+        }
+
+        for pkg in &self.suppress {
+            filters.set(pkg.clone(), TargetLevel::Fixed(LevelFilter::Off));
+        }
+
+        // Anything set explicitly wins over the lists above.
+        for (target, level) in &self.levels {
+            filters.set(target.clone(), TargetLevel::Fixed(*level));
+        }
+
+        // And the targets in `RUST_LOG` win over everything.
+        let invalid_directives = directives.invalid;
+        for (target, level) in directives.targets {
+            filters.set(target.into(), TargetLevel::Fixed(level));
+        }
+
+        // Declare the main logging module, the levels are checked against
+        // the shared filters so they can be changed later on.
+        let mut dispatch = fern::Dispatch::new()
+            .filter(|metadata| filter::read().enabled(metadata));
+
+        if let Some(Callback(filter)) = &self.filter {
+            let filter = Arc::clone(filter);
+            dispatch = dispatch.filter(move |metadata| filter(metadata));
+        }
+
+        // There's no stdout in the browser, so everything that would've gone
+        // there goes to the console instead.
+        let browser = cfg!(all(target_arch = "wasm32", feature = "wasm"));
+        #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+        if self.stdout {
+            dispatch = dispatch.chain(
+                self.formatter(layout, stdout_format, false)
+                    .level(self.stdout_level)
+                    .chain(crate::wasm::console()),
+            );
+        }
+
+        // Ensure that stdout gets colored logging info, if warnings and
+        // errors should be split off then they go to stderr instead.
+        let stdout_color = format::should_color(color, &std::io::stdout());
+        if !self.stdout || browser {
+            // Nothing goes to the terminal at all.
+        } else if self.stderr {
+            let stderr_color = format::should_color(color, &std::io::stderr());
+            dispatch = dispatch
+                .chain(
+                    self.formatter(layout, stdout_format, stdout_color)
+                        .level(self.stdout_level)
+                        .filter(|metadata| metadata.level() > log::Level::Warn)
+                        .chain(std::io::stdout()),
+                )
+                .chain(
+                    self.formatter(layout, stdout_format, stderr_color)
+                        .level(self.stdout_level.min(LevelFilter::Warn))
+                        .chain(std::io::stderr()),
+                );
+        } else {
+            dispatch = dispatch.chain(
+                self.formatter(layout, stdout_format, stdout_color)
+                    .level(self.stdout_level)
+                    .chain(std::io::stdout()),
+            );
+        }
+
+        // If any log files are set, use them. Each file gets its own formatter
+        // so the escape codes don't end up in the file. Browsers don't
+        // have any files to write to.
+        for log_file in self.log_files.iter().filter(|_| !browser) {
+            dispatch = dispatch.chain(
+                self.formatter(file_layout, log_file.format.unwrap_or(file_format), false)
+                    .level(log_file.level.min(self.file_level))
+                    .chain(self.open_log_file(&log_file.path).map_err(|source| {
+                        SetupError::LogFile {
+                            path: log_file.path.clone(),
+                            source,
+                        }
+                    })?),
+            );
+        }
+
+        // syslog adds the date and level itself.
+        #[cfg(all(unix, feature = "syslog"))]
+        if let Some(facility) = self.syslog {
+            let formatter = syslog::Formatter3164 {
+                facility,
+                hostname: None,
+                process: self.process_name(),
+                pid: std::process::id(),
+            };
+
+            dispatch = dispatch.chain(
+                fern::Dispatch::new()
+                    .format(|out, message, record| {
+                        out.finish(format_args!("{}: {message}", record.target()))
+                    })
+                    .chain(syslog::unix(formatter)?),
+            );
+        }
+
+        #[cfg(all(target_os = "linux", feature = "journald"))]
+        if self.journald {
+            let journald = Journald::connect(self.process_name()).map_err(SetupError::Journald)?;
+            dispatch = dispatch.chain(fern::Output::call(move |record| journald.send(record)));
+        }
+
+        #[cfg(all(windows, feature = "eventlog"))]
+        if let Some(source) = &self.event_log {
+            let event_log = EventLog::register(source).map_err(SetupError::EventLog)?;
+            dispatch = dispatch.chain(fern::Output::call(move |record| event_log.report(record)));
+        }
+
+        #[cfg(feature = "network")]
+        for (addr, transport, kind) in &self.network {
+            let gelf = *kind == OutputFormat::Gelf;
+            let writer = NetworkWriter::connect(*addr, *transport, gelf).map_err(SetupError::Network)?;
+            let writer: Box<dyn Write + Send> = Box::new(writer);
+
+            dispatch = dispatch.chain(self.formatter(file_layout, *kind, false).chain(writer));
+        }
+
+        for writer in &self.writers {
+            let writer: Box<dyn Write + Send> = Box::new(writer.clone());
+            dispatch = dispatch.chain(self.formatter(file_layout, file_format, false).chain(writer));
+        }
+
+        dispatch = dispatch.chain(metrics::output());
+
+        // Remember the last few lines, using the plain text layout.
+        if self.ring_buffer > 0 {
+            dispatch = dispatch.chain(
+                self.formatter(file_layout, OutputFormat::Text, false)
+                    .chain(recent::output()),
+            );
+        }
+
+        for Callback(callback) in &self.on_record {
+            let callback = Arc::clone(callback);
+            dispatch = dispatch.chain(fern::Output::call(move |record| callback(record)));
+        }
+
+        // Keep hold of the raw records, before they are formatted.
+        #[cfg(feature = "test-util")]
+        if let Some(Callback(capture)) = &self.capture {
+            let capture = Arc::clone(capture);
+            dispatch = dispatch.chain(fern::Output::call(move |record| capture(record)));
+        }
+
+        // The ceiling is checked after everything else.
+        if let Some(max_level) = self.max_level {
+            filters.cap(max_level);
+        }
+
+        let (_, mut logger) = dispatch.into_log();
+        if let Some(window) = self.dedup {
+            logger = Box::new(Dedup::new(logger, window).map_err(SetupError::Thread)?);
+        }
+
+        #[cfg(feature = "async")]
+        if let Some((capacity, overflow)) = self.background {
+            logger = Box::new(Background::new(logger, capacity, overflow).map_err(SetupError::Thread)?);
+        }
+
+        // Filter before deduplicating, so dropped records aren't counted.
+        if let Some(Callback(filter)) = &self.filter_record {
+            logger = Box::new(RecordFilter {
+                inner: logger,
+                filter: Arc::clone(filter),
+            });
+        }
+
+        // Nothing can fail past here, so only now is the shared state
+        // changed. A failed `init` leaves everything as it was.
+        switch::register()?;
+        format::start_clock();
+
+        for (key, value) in &self.context {
+            context::set_context(key.clone(), value);
+        }
+
+        if self.ring_buffer > 0 {
+            recent::start(self.ring_buffer);
+        }
+
+        // Apply all the logging info
+        let max_level = filters.max_level();
+        filter::install(filters);
+
+        switch::install(max_level, logger);
+
+        #[cfg(feature = "tracing")]
+        subscriber::install();
+
+        env::warn_invalid(&invalid_directives, &overrides.invalid);
+
+        // Set a nicer looking panic hook, so incase there ever is a panic, it'll
+        // be handled nicer.
+        #[cfg(all(feature = "panic-hook", not(feature = "no-panic-handler")))]
+        if self.panic_hook {
+            panic_hook::install(PanicOptions {
+                exit: self.exit_on_panic,
+                exit_code: self.panic_exit_code,
+                chain: self.chain_panic_hook,
+                structured: self.structured_panics,
+                level: self.panic_level,
+                #[cfg(feature = "panic-webhook")]
+                webhook: self.panic_webhook,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Create a dispatch for a single output, using the user's formatter
+    /// if there is one.
+    fn formatter(&self, layout: Layout, kind: OutputFormat, colored: bool) -> fern::Dispatch {
+        match &self.format {
+            Some(Callback(format)) => {
+                let format = Arc::clone(format);
+                fern::Dispatch::new().format(move |out, message, record| format(out, message, record))
+            }
+            None => match kind {
+                OutputFormat::Text => fern::Dispatch::new().format(format::format_line(layout, colored)),
+                OutputFormat::Json => fern::Dispatch::new().format(format::format_json(layout)),
+                OutputFormat::Logfmt => fern::Dispatch::new().format(format::format_logfmt(layout)),
+                OutputFormat::Gelf => fern::Dispatch::new().format(format::format_gelf(layout)),
+            },
+        }
+    }
+
+    /// The name to send to syslog or journald, this is the root
+    /// crate if there is one.
+    #[cfg(any(all(unix, feature = "syslog"), all(target_os = "linux", feature = "journald")))]
+    fn process_name(&self) -> String {
+        if let Some(root) = &self.root {
+            return root.to_string();
+        }
+
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| String::from("rust"))
+    }
+
+    /// Open the log file, rotating it if needed.
+    fn open_log_file(&self, path: &Path) -> io::Result<fern::Output> {
+        #[cfg(feature = "gzip")]
+        let compress = self.compress;
+        #[cfg(not(feature = "gzip"))]
+        let compress = false;
+
+        let file: Box<dyn Write + Send> = match self.rotation {
+            // fern already puts a plain file behind a `BufWriter` that is
+            // flushed after every line.
+            Rotation::Never if self.file_buffer == 0 => {
+                return Ok(file::open(path, self.file_mode)?.into());
+            }
+            Rotation::Never => Box::new(file::open(path, self.file_mode)?),
+            Rotation::Size { max_bytes, keep } => Box::new(SizeRotatingFile::new(
+                path,
+                self.file_mode,
+                max_bytes,
+                keep,
+                compress,
+                self.retention,
+            )?),
+            Rotation::Daily | Rotation::Hourly => {
+                let hourly = self.rotation == Rotation::Hourly;
+                Box::new(TimeRotatingFile::new(
+                    path,
+                    self.file_mode,
+                    hourly,
+                    self.timezone,
+                    compress,
+                    self.retention,
+                )?)
+            }
+        };
+
+        if self.file_buffer == 0 {
+            return Ok(file.into());
+        }
+
+        let file: Box<dyn Write + Send> = Box::new(BufferedFile::new(file, self.file_buffer));
+        Ok(file.into())
+    }
+}
@@ -0,0 +1,95 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::{LoggerBuilder, LoggerGuard, SetupError};
+use log::{Level, LevelFilter};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Only one capture can be active at a time, this makes tests running
+/// in parallel wait their turn rather than stepping on each other.
+static CAPTURE_LOCK: Mutex<()> = Mutex::new(());
+
+/// A captured record, the level, target and message.
+pub type CapturedRecord = (Level, String, String);
+
+/// Captures every logged record in memory, see [`capture`].
+///
+/// The logger is reset when this is dropped.
+#[must_use = "the logger is reset as soon as the capture is dropped"]
+pub struct Capture {
+    records: Arc<Mutex<Vec<CapturedRecord>>>,
+    // Reset the logger before letting the next capture start.
+    _guard: LoggerGuard,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Capture {
+    pub(crate) fn start(
+        builder: LoggerBuilder,
+        records: Arc<Mutex<Vec<CapturedRecord>>>,
+    ) -> Result<Self, SetupError> {
+        // A test that panicked while capturing shouldn't break the rest.
+        let lock = CAPTURE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+        Ok(Self {
+            records,
+            _guard: builder.init_scoped()?,
+            _lock: lock,
+        })
+    }
+
+    /// Everything that has been logged so far.
+    pub fn records(&self) -> Vec<CapturedRecord> {
+        self.records.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    /// Throw away everything that has been logged so far.
+    pub fn clear(&self) {
+        self.records.lock().unwrap_or_else(|err| err.into_inner()).clear();
+    }
+}
+
+/// Install a logger that captures every record in memory instead of
+/// printing it, so tests can check what was logged. Requires the
+/// `test-util` feature.
+///
+/// Only one capture can be active at once, so this waits until any other
+/// capture has been dropped. Use [`LoggerBuilder::capture`] to capture
+/// with a different configuration.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::Level;
+///
+/// let capture = sir_logger::capture().expect("Unable to setup logger");
+/// log::error!("it broke");
+///
+/// let records = capture.records();
+/// assert_eq!(records[0].0, Level::Error);
+/// assert!(records[0].2.contains("broke"));
+/// ```
+pub fn capture() -> Result<Capture, SetupError> {
+    LoggerBuilder::new()
+        .level(LevelFilter::Trace)
+        .panic_hook(false)
+        .capture()
+}
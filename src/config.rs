@@ -0,0 +1,107 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::env;
+use crate::{LoggerBuilder, OutputFormat, Rotation};
+use log::LevelFilter;
+use toml::{Table, Value};
+
+/// Setup `builder` from the settings in a config file, see
+/// [`LoggerBuilder::from_file`] for what can be in it.
+pub(crate) fn apply(mut builder: LoggerBuilder, config: &str) -> Result<LoggerBuilder, String> {
+    let config: Table = config.parse().map_err(|err: toml::de::Error| err.message().trim().replace('\n', ", "))?;
+
+    for (key, value) in config {
+        builder = match key.as_str() {
+            "level" => builder.level(level(&key, &value)?),
+            "root" => builder.root(string(&key, &value)?.to_string()),
+            "color" => {
+                let color = string(&key, &value)?;
+                builder.color(env::parse_color(color).ok_or_else(|| expected(&key, "\"auto\", \"always\" or \"never\""))?)
+            }
+            "stdout" => builder.stdout(value.as_bool().ok_or_else(|| expected(&key, "true or false"))?),
+            "stdout_level" => builder.stdout_level(level(&key, &value)?),
+            "stdout_format" => builder.stdout_format(format(&key, &value)?),
+            "file" => builder.log_file(string(&key, &value)?),
+            "file_level" => builder.file_level(level(&key, &value)?),
+            "file_format" => builder.file_format(format(&key, &value)?),
+            "rotation" => builder.rotation(rotation(&value)?),
+            "levels" => {
+                let targets = value.as_table().ok_or_else(|| expected(&key, "a table of targets and levels"))?;
+                for (target, value) in targets {
+                    builder = builder.level_for(target.clone(), level(target, value)?);
+                }
+                builder
+            }
+            _ => return Err(format!("unknown setting `{key}`")),
+        };
+    }
+
+    Ok(builder)
+}
+
+fn expected(key: &str, what: &str) -> String {
+    format!("`{key}` should be {what}")
+}
+
+fn string<'a>(key: &str, value: &'a Value) -> Result<&'a str, String> {
+    value.as_str().ok_or_else(|| expected(key, "a string"))
+}
+
+fn level(key: &str, value: &Value) -> Result<LevelFilter, String> {
+    crate::parse_level(string(key, value)?).ok_or_else(|| expected(key, "a level like \"info\" or \"off\""))
+}
+
+fn format(key: &str, value: &Value) -> Result<OutputFormat, String> {
+    env::parse_format(string(key, value)?).ok_or_else(|| expected(key, "\"text\", \"json\", \"logfmt\" or \"gelf\""))
+}
+
+/// Either `"never"`, `"daily"` or `"hourly"`, or a table with the
+/// `max_bytes` and `keep` of [`Rotation::Size`].
+fn rotation(value: &Value) -> Result<Rotation, String> {
+    const EXPECTED: &str = "\"never\", \"daily\", \"hourly\" or { max_bytes = .., keep = .. }";
+
+    if let Some(table) = value.as_table() {
+        let number = |key: &str| {
+            let value = table.get(key).ok_or_else(|| format!("`rotation` is missing `{key}`"))?;
+            value
+                .as_integer()
+                .and_then(|number| u64::try_from(number).ok())
+                .ok_or_else(|| expected(&format!("rotation.{key}"), "a whole number"))
+        };
+
+        if let Some(key) = table.keys().find(|key| *key != "max_bytes" && *key != "keep") {
+            return Err(format!("unknown setting `rotation.{key}`"));
+        }
+
+        return Ok(Rotation::Size {
+            max_bytes: number("max_bytes")?,
+            keep: number("keep")? as usize,
+        });
+    }
+
+    match value.as_str() {
+        Some("never") => Ok(Rotation::Never),
+        Some("daily") => Ok(Rotation::Daily),
+        Some("hourly") => Ok(Rotation::Hourly),
+        _ => Err(expected("rotation", EXPECTED)),
+    }
+}
@@ -0,0 +1,185 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The fields added to every record, in the order they were set.
+static GLOBAL: RwLock<Vec<(Cow<'static, str>, String)>> = RwLock::new(Vec::new());
+
+fn read() -> RwLockReadGuard<'static, Vec<(Cow<'static, str>, String)>> {
+    GLOBAL.read().unwrap_or_else(|err| err.into_inner())
+}
+
+fn write() -> RwLockWriteGuard<'static, Vec<(Cow<'static, str>, String)>> {
+    GLOBAL.write().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Add a field to every line that is logged from now on, e.g.
+/// `service=api`. Setting a key again replaces its value.
+///
+/// The fields go at the end of [`OutputFormat::Text`](crate::OutputFormat::Text)
+/// and [`OutputFormat::Logfmt`](crate::OutputFormat::Logfmt) lines, and are
+/// extra fields in JSON and GELF.
+///
+/// ## Example
+///
+/// ```rust
+/// use sir_logger::{LoggerBuilder, Timestamp};
+///
+/// LoggerBuilder::new()
+///     .timestamp(Timestamp::None)
+///     .ring_buffer(1)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// sir_logger::set_context("instance", 7);
+/// log::info!(target: "app", "started");
+///
+/// assert_eq!(sir_logger::recent_logs(), ["[INFO app] started instance=7"]);
+/// ```
+pub fn set_context(key: impl Into<Cow<'static, str>>, value: impl ToString) {
+    let key = key.into();
+    let value = value.to_string();
+    let mut fields = write();
+
+    match fields.iter_mut().find(|(name, _)| *name == key) {
+        Some((_, old)) => *old = value,
+        None => fields.push((key, value)),
+    }
+}
+
+/// Stop adding the field `key` to every line, see [`set_context`].
+pub fn remove_context(key: &str) {
+    write().retain(|(name, _)| name != key);
+}
+
+/// Remove every field from [`set_context`], when the logger is reset.
+pub(crate) fn clear() {
+    write().clear();
+}
+
+thread_local! {
+    /// The fields from [`with_context`] on this thread, with the id of
+    /// the guard that removes them.
+    static SCOPED: RefCell<Vec<(u64, Cow<'static, str>, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// The id for the next [`ContextGuard`] on this thread.
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+
+    /// The fields of a record that is being written by a different thread.
+    #[cfg(feature = "async")]
+    static LOGGED_WITH: RefCell<Option<Vec<(String, String)>>> = const { RefCell::new(None) };
+}
+
+/// Add a field to every line logged on this thread, until the returned
+/// guard is dropped, e.g. to tag everything logged while handling a request.
+/// These are added after the fields from [`set_context`], and win if they
+/// have the same key.
+///
+/// ## Example
+///
+/// ```rust
+/// use sir_logger::{LoggerBuilder, Timestamp};
+///
+/// LoggerBuilder::new()
+///     .timestamp(Timestamp::None)
+///     .ring_buffer(2)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// {
+///     let _context = sir_logger::with_context("request_id", 42);
+///     log::info!(target: "app", "handling request");
+/// }
+/// log::info!(target: "app", "idle");
+///
+/// assert_eq!(
+///     sir_logger::recent_logs(),
+///     ["[INFO app] handling request request_id=42", "[INFO app] idle"],
+/// );
+/// ```
+pub fn with_context(key: impl Into<Cow<'static, str>>, value: impl ToString) -> ContextGuard {
+    let id = NEXT_ID.with(|next| {
+        let id = next.get();
+        next.set(id + 1);
+        id
+    });
+
+    SCOPED.with(|scoped| scoped.borrow_mut().push((id, key.into(), value.to_string())));
+
+    ContextGuard {
+        id,
+        _not_send: PhantomData,
+    }
+}
+
+/// Removes a field added by [`with_context`] when dropped.
+#[derive(Debug)]
+#[must_use = "the field is removed as soon as the guard is dropped"]
+pub struct ContextGuard {
+    id: u64,
+    // The field belongs to this thread, so the guard has to stay on it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // Guards can be dropped in any order, so only remove this one.
+        let _ = SCOPED.try_with(|scoped| scoped.borrow_mut().retain(|(id, _, _)| *id != self.id));
+    }
+}
+
+/// All the fields to add to the current record.
+pub(crate) fn fields() -> Vec<(String, String)> {
+    #[cfg(feature = "async")]
+    if let Some(fields) = LOGGED_WITH.with(|with| with.borrow().clone()) {
+        return fields;
+    }
+
+    let mut fields: Vec<(String, String)> = read()
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.clone()))
+        .collect();
+
+    SCOPED.with(|scoped| {
+        for (_, key, value) in scoped.borrow().iter() {
+            match fields.iter_mut().find(|(name, _)| name == key) {
+                Some((_, old)) => old.clone_from(value),
+                None => fields.push((key.to_string(), value.clone())),
+            }
+        }
+    });
+
+    fields
+}
+
+/// Run `log`, with every record using `fields` instead of the
+/// fields of the current thread.
+#[cfg(feature = "async")]
+pub(crate) fn logged_with<R>(fields: Vec<(String, String)>, log: impl FnOnce() -> R) -> R {
+    let previous = LOGGED_WITH.with(|with| with.replace(Some(fields)));
+    let result = log();
+    LOGGED_WITH.with(|with| *with.borrow_mut() = previous);
+    result
+}
@@ -0,0 +1,191 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use log::{Level, Log, Metadata, Record};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// The last line that was logged, and how many times it has been
+/// repeated since.
+struct Last {
+    level: Level,
+    target: String,
+    message: String,
+    since: Instant,
+    repeated: usize,
+}
+
+/// Wraps a logger, dropping lines that are the same as the one before
+/// and logging how many were dropped instead.
+pub(crate) struct Dedup {
+    shared: Arc<Shared>,
+    /// Dropped to stop the thread that logs the summaries on time.
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// The parts of [`Dedup`] that the summary thread needs as well.
+struct Shared {
+    inner: Box<dyn Log>,
+    window: Duration,
+    last: Mutex<Option<Last>>,
+}
+
+impl Dedup {
+    pub(crate) fn new(inner: Box<dyn Log>, window: Duration) -> std::io::Result<Self> {
+        let shared = Arc::new(Shared {
+            inner,
+            window,
+            last: Mutex::new(None),
+        });
+
+        let (stop, stopped) = mpsc::channel();
+        let thread_shared = Arc::clone(&shared);
+        let thread = std::thread::Builder::new()
+            .name(String::from("sir_logger-dedup"))
+            .spawn(move || run(&thread_shared, stopped))?;
+
+        Ok(Self {
+            shared,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Log the summaries of lines that stopped being logged, until the
+/// logger is dropped. Without this a line that is repeated and then
+/// never logged again would only get its summary on the next flush.
+fn run(shared: &Shared, stopped: mpsc::Receiver<()>) {
+    let mut wait = shared.window;
+
+    while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(wait) {
+        wait = shared.summarize_expired();
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Option<Last>> {
+        self.last.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Log the summary of however many lines were dropped.
+    fn summarize(&self, last: &Last) {
+        if last.repeated == 0 {
+            return;
+        }
+
+        self.inner.log(
+            &Record::builder()
+                .level(last.level)
+                .target(&last.target)
+                .args(format_args!("... (repeated {} times)", last.repeated))
+                .build(),
+        );
+    }
+
+    /// Log the summary once the window of the last line is over, and
+    /// return how long until it should be checked again.
+    fn summarize_expired(&self) -> Duration {
+        let mut last = self.lock();
+        let Some(last) = last.as_mut() else {
+            return self.window;
+        };
+
+        let elapsed = last.since.elapsed();
+        if elapsed < self.window {
+            return self.window - elapsed;
+        }
+
+        // Any repeats from now on are counted towards the next summary.
+        self.summarize(last);
+        last.repeated = 0;
+        last.since = Instant::now();
+        self.window
+    }
+}
+
+impl Log for Dedup {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.shared.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let shared = &*self.shared;
+        if !shared.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = record.args().to_string();
+        let mut last = shared.lock();
+
+        if let Some(previous) = last.as_mut() {
+            let same = previous.level == record.level()
+                && previous.target == record.target()
+                && previous.message == message;
+
+            // Repeats are only dropped within the window, after that the
+            // summary is logged so it is clear the line is still showing up.
+            if same && previous.since.elapsed() < shared.window {
+                previous.repeated += 1;
+                return;
+            }
+        }
+
+        // The lock is held while logging, so the summary and the
+        // next line can't be split up by another thread.
+        if let Some(previous) = last.take() {
+            shared.summarize(&previous);
+        }
+
+        shared.inner.log(record);
+        *last = Some(Last {
+            level: record.level(),
+            target: record.target().to_string(),
+            message,
+            since: Instant::now(),
+            repeated: 0,
+        });
+    }
+
+    fn flush(&self) {
+        // Don't lose the count of the lines that were dropped at the end.
+        if let Some(last) = self.shared.lock().as_mut() {
+            self.shared.summarize(last);
+            last.repeated = 0;
+        }
+
+        self.shared.inner.flush();
+    }
+}
+
+impl Drop for Dedup {
+    /// Stop the summary thread, so it doesn't outlive the logger.
+    fn drop(&mut self) {
+        self.stop.take();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
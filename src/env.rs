@@ -0,0 +1,298 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::{ColorChoice, OutputFormat};
+use log::LevelFilter;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Everything that was set in `RUST_LOG`.
+#[derive(Debug, Default)]
+pub(crate) struct Directives {
+    /// The level from a bare `info` or `warn`, if there was one.
+    pub(crate) level: Option<LevelFilter>,
+    /// The levels from `target=level` pairs.
+    pub(crate) targets: Vec<(String, LevelFilter)>,
+    /// The directives that were ignored, so they can be warned about.
+    pub(crate) invalid: Vec<String>,
+}
+
+/// Read and parse `RUST_LOG`, if it isn't set then nothing is overridden.
+pub(crate) fn rust_log() -> Directives {
+    std::env::var("RUST_LOG")
+        .map(|value| parse(&value))
+        .unwrap_or_default()
+}
+
+/// The settings from the `SIR_LOG_*` variables, see
+/// [`LoggerBuilder::init`](crate::LoggerBuilder::init).
+#[derive(Debug, Default)]
+pub(crate) struct Overrides {
+    pub(crate) file: Option<PathBuf>,
+    pub(crate) color: Option<ColorChoice>,
+    pub(crate) format: Option<OutputFormat>,
+    /// The variables that couldn't be parsed, so they can be warned about.
+    pub(crate) invalid: Vec<String>,
+}
+
+/// Read `SIR_LOG_FILE`, `SIR_LOG_COLOR` and `SIR_LOG_FORMAT`.
+pub(crate) fn overrides() -> Overrides {
+    parse_overrides(|name| std::env::var_os(name))
+}
+
+/// Parse the `SIR_LOG_*` variables, looking each one up with `var`. Empty
+/// ones are the same as not being set.
+fn parse_overrides(var: impl Fn(&str) -> Option<OsString>) -> Overrides {
+    let var = |name: &str| var(name).filter(|value| !value.is_empty());
+    let mut invalid = Vec::new();
+
+    Overrides {
+        file: var("SIR_LOG_FILE").map(PathBuf::from),
+        color: parsed_var("SIR_LOG_COLOR", var("SIR_LOG_COLOR"), parse_color, &mut invalid),
+        format: parsed_var("SIR_LOG_FORMAT", var("SIR_LOG_FORMAT"), parse_format, &mut invalid),
+        invalid,
+    }
+}
+
+/// Parse the `value` of the variable `name`, keeping it in `invalid` when
+/// it can't be parsed.
+fn parsed_var<T>(
+    name: &str,
+    value: Option<OsString>,
+    parse: fn(&str) -> Option<T>,
+    invalid: &mut Vec<String>,
+) -> Option<T> {
+    let value = value?;
+    let parsed = value.to_str().and_then(parse);
+    if parsed.is_none() {
+        invalid.push(format!("{name}={}", value.to_string_lossy()));
+    }
+
+    parsed
+}
+
+/// Parse `"auto"`, `"always"` or `"never"`.
+pub(crate) fn parse_color(color: &str) -> Option<ColorChoice> {
+    match color.trim().to_ascii_lowercase().as_str() {
+        "auto" => Some(ColorChoice::Auto),
+        "always" => Some(ColorChoice::Always),
+        "never" => Some(ColorChoice::Never),
+        _ => None,
+    }
+}
+
+/// Parse `"text"`, `"json"`, `"logfmt"` or `"gelf"`.
+pub(crate) fn parse_format(format: &str) -> Option<OutputFormat> {
+    match format.trim().to_ascii_lowercase().as_str() {
+        "text" => Some(OutputFormat::Text),
+        "json" => Some(OutputFormat::Json),
+        "logfmt" => Some(OutputFormat::Logfmt),
+        "gelf" => Some(OutputFormat::Gelf),
+        _ => None,
+    }
+}
+
+/// Parse a list of env_logger style directives, e.g. `info,hyper=warn`.
+///
+/// A bare level sets the global level, a bare target turns on everything
+/// for that target and `target=level` sets the level of just that target.
+/// Anything that can't be parsed is ignored, and kept in `invalid`.
+pub(crate) fn parse(value: &str) -> Directives {
+    let mut directives = Directives::default();
+
+    for directive in value.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+        match directive.split_once('=') {
+            Some((target, level)) => match parse_level(level) {
+                Some(level) if is_target(target.trim()) => {
+                    directives.targets.push((target.trim().to_string(), level))
+                }
+                _ => directives.invalid.push(directive.to_string()),
+            },
+            None => match parse_level(directive) {
+                Some(level) => directives.level = Some(level),
+                None if is_target(directive) && !LEVEL_LIKE.iter().any(|l| directive.eq_ignore_ascii_case(l)) => {
+                    directives.targets.push((directive.to_string(), LevelFilter::Trace))
+                }
+                None => directives.invalid.push(directive.to_string()),
+            },
+        }
+    }
+
+    directives
+}
+
+/// Words that were almost certainly meant as a level, not a crate name,
+/// e.g. `RUST_LOG=verbose`.
+const LEVEL_LIKE: [&str; 10] = [
+    "verbose", "all", "none", "quiet", "silent", "warnings", "errors", "fatal", "critical", "notice",
+];
+
+/// Whether `target` could be a module path, e.g. `my_app::db`.
+fn is_target(target: &str) -> bool {
+    !target.is_empty()
+        && target.split("::").all(|part| {
+            part.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        })
+}
+
+/// Warn about the directives in `RUST_LOG` and the `SIR_LOG_*` variables
+/// that were ignored, just once so it isn't repeated every time the logger
+/// is setup again.
+pub(crate) fn warn_invalid(directives: &[String], overrides: &[String]) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+
+    if (directives.is_empty() && overrides.is_empty()) || WARNED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    if !directives.is_empty() {
+        log::warn!(
+            target: "sir_logger",
+            "Ignoring these parts of RUST_LOG, they aren't valid targets or levels: {}",
+            directives.join(", ")
+        );
+    }
+
+    if !overrides.is_empty() {
+        log::warn!(target: "sir_logger", "Ignoring invalid environment variables: {}", overrides.join(", "));
+    }
+}
+
+/// Parse a level name like `"debug"`, e.g. from a config file or a command
+/// line flag. Case doesn't matter, and `warning` and `err` work as well as
+/// `warn` and `error`. This is what `RUST_LOG` is parsed with.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+/// use sir_logger::parse_level;
+///
+/// assert_eq!(parse_level("Debug"), Some(LevelFilter::Debug));
+/// assert_eq!(parse_level("warning"), Some(LevelFilter::Warn));
+/// assert_eq!(parse_level("loud"), None);
+/// ```
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    let level = level.trim();
+
+    if level.eq_ignore_ascii_case("warning") {
+        return Some(LevelFilter::Warn);
+    }
+
+    if level.eq_ignore_ascii_case("err") {
+        return Some(LevelFilter::Error);
+    }
+
+    level.parse::<LevelFilter>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(directives: &Directives) -> Vec<(&str, LevelFilter)> {
+        directives.targets.iter().map(|(target, level)| (target.as_str(), *level)).collect()
+    }
+
+    #[test]
+    fn global_and_target_levels() {
+        let directives = parse("warn,my_crate=trace");
+        assert_eq!(directives.level, Some(LevelFilter::Warn));
+        assert_eq!(targets(&directives), [("my_crate", LevelFilter::Trace)]);
+        assert!(directives.invalid.is_empty());
+    }
+
+    #[test]
+    fn bare_target_turns_everything_on() {
+        let directives = parse("my_crate::db");
+        assert_eq!(directives.level, None);
+        assert_eq!(targets(&directives), [("my_crate::db", LevelFilter::Trace)]);
+    }
+
+    #[test]
+    fn invalid_parts_are_kept_and_the_rest_applies() {
+        let directives = parse("verbose, my_crate=loud,=debug,my crate,debug");
+        assert_eq!(directives.level, Some(LevelFilter::Debug));
+        assert!(directives.targets.is_empty());
+        assert_eq!(directives.invalid, ["verbose", "my_crate=loud", "=debug", "my crate"]);
+    }
+
+    #[test]
+    fn level_names_are_forgiving() {
+        let directives = parse("WARNING,my_crate=Err");
+        assert_eq!(directives.level, Some(LevelFilter::Warn));
+        assert_eq!(targets(&directives), [("my_crate", LevelFilter::Error)]);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("auto"), Some(ColorChoice::Auto));
+        assert_eq!(parse_color(" Always "), Some(ColorChoice::Always));
+        assert_eq!(parse_color("NEVER"), Some(ColorChoice::Never));
+        assert_eq!(parse_color("sometimes"), None);
+    }
+
+    fn overrides_from(vars: &[(&str, &str)]) -> Overrides {
+        parse_overrides(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value))
+        })
+    }
+
+    #[test]
+    fn overrides_are_parsed() {
+        let overrides = overrides_from(&[
+            ("SIR_LOG_FILE", "app.log"),
+            ("SIR_LOG_COLOR", "always"),
+            ("SIR_LOG_FORMAT", "json"),
+        ]);
+        assert_eq!(overrides.file, Some(PathBuf::from("app.log")));
+        assert_eq!(overrides.color, Some(ColorChoice::Always));
+        assert_eq!(overrides.format, Some(OutputFormat::Json));
+        assert!(overrides.invalid.is_empty());
+    }
+
+    #[test]
+    fn invalid_overrides_are_kept() {
+        let overrides = overrides_from(&[("SIR_LOG_COLOR", "sometimes"), ("SIR_LOG_FORMAT", "json")]);
+        assert_eq!(overrides.color, None);
+        assert_eq!(overrides.format, Some(OutputFormat::Json));
+        assert_eq!(overrides.invalid, ["SIR_LOG_COLOR=sometimes"]);
+    }
+
+    #[test]
+    fn empty_overrides_are_unset() {
+        let overrides = overrides_from(&[("SIR_LOG_FILE", ""), ("SIR_LOG_COLOR", "")]);
+        assert_eq!(overrides.file, None);
+        assert_eq!(overrides.color, None);
+        assert!(overrides.invalid.is_empty());
+    }
+
+    #[test]
+    fn empty_parts_are_skipped() {
+        let directives = parse(" , ,info,");
+        assert_eq!(directives.level, Some(LevelFilter::Info));
+        assert!(directives.invalid.is_empty());
+    }
+}
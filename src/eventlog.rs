@@ -0,0 +1,102 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use log::Level;
+use std::io;
+use std::ptr;
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::System::EventLog::{
+    DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+    EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE, REPORT_EVENT_TYPE,
+};
+
+/// Writes records to the Windows Event Log, under a single event source.
+pub(crate) struct EventLog {
+    handle: HANDLE,
+}
+
+// SAFETY: event log handles can be used from any thread.
+unsafe impl Send for EventLog {}
+unsafe impl Sync for EventLog {}
+
+impl EventLog {
+    /// Open the event source called `source`, this is what shows up in
+    /// the "Source" column of the Event Viewer.
+    pub(crate) fn register(source: &str) -> io::Result<Self> {
+        let source = wide(source);
+
+        // SAFETY: `source` is a nul terminated UTF-16 string that
+        // outlives the call.
+        let handle = unsafe { RegisterEventSourceW(ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { handle })
+    }
+
+    /// Write a single record, errors are ignored as there's nowhere
+    /// left to report them.
+    pub(crate) fn report(&self, record: &log::Record) {
+        let message = wide(&format!("{}: {}", record.target(), record.args()));
+        let strings = [message.as_ptr()];
+
+        // SAFETY: the handle is open until `self` is dropped, and
+        // `strings` holds a single nul terminated string.
+        unsafe {
+            ReportEventW(
+                self.handle,
+                event_type(record.level()),
+                0,
+                0,
+                ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                ptr::null(),
+            );
+        }
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from `RegisterEventSourceW` and
+        // isn't used again.
+        unsafe {
+            DeregisterEventSource(self.handle);
+        }
+    }
+}
+
+/// The Event Log only knows about errors, warnings and information.
+fn event_type(level: Level) -> REPORT_EVENT_TYPE {
+    match level {
+        Level::Error => EVENTLOG_ERROR_TYPE,
+        Level::Warn => EVENTLOG_WARNING_TYPE,
+        Level::Info | Level::Debug | Level::Trace => EVENTLOG_INFORMATION_TYPE,
+    }
+}
+
+/// Convert `value` to a nul terminated UTF-16 string.
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain([0]).collect()
+}
@@ -0,0 +1,727 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::format::{OutputFormat, Timezone};
+use log::LevelFilter;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// A file to write logs to, see [`LoggerBuilder::add_log_file`](crate::LoggerBuilder::add_log_file).
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+/// use sir_logger::{LogFile, OutputFormat};
+///
+/// // A file that only gets errors.
+/// let errors = LogFile::new("errors.log").level(LevelFilter::Error);
+///
+/// // And one for everything from `info` up, as JSON.
+/// let json = LogFile::new("log.json")
+///     .level(LevelFilter::Info)
+///     .format(OutputFormat::Json);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub(crate) path: PathBuf,
+    pub(crate) level: LevelFilter,
+    pub(crate) format: Option<OutputFormat>,
+}
+
+impl LogFile {
+    /// Log to the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            level: LevelFilter::Trace,
+            format: None,
+        }
+    }
+
+    /// Only write records at `level` or above to this file. This is applied
+    /// on top of the normal level filtering, so the file can't get anything
+    /// that the [`level`](crate::LoggerBuilder::level) drops.
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// The layout of the lines in this file, instead of the
+    /// [`file_format`](crate::LoggerBuilder::file_format).
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+}
+
+/// How the log file is opened when the logger starts.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use sir_logger::{FileMode, LoggerBuilder};
+///
+/// LoggerBuilder::new()
+///     .log_file("test.log")
+///     .file_mode(FileMode::Truncate)
+///     .init()
+///     .expect("Unable to setup logger");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileMode {
+    /// Keep whatever is already in the file and add to the end of it,
+    /// so restarting doesn't wipe the history.
+    #[default]
+    Append,
+    /// Empty the file first, useful for tests.
+    Truncate,
+}
+
+/// When the log file should be rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// Never rotate, the log file will keep on growing.
+    #[default]
+    Never,
+    /// Rotate once the file would grow past `max_bytes`. The old file is
+    /// renamed to `log.txt.1`, the one before that to `log.txt.2` and so
+    /// on, keeping at most `keep` old files around.
+    Size {
+        /// The largest the log file is allowed to get, in bytes.
+        max_bytes: u64,
+        /// How many rotated files to keep.
+        keep: usize,
+    },
+    /// Start a new file every day. The date is put before the extension of
+    /// the log file, so `logs/app.txt` becomes `logs/app-2025-06-01.txt`.
+    Daily,
+    /// Start a new file every hour, named like `logs/app-2025-06-01-13.txt`.
+    Hourly,
+}
+
+/// Which rotated files to delete, on top of the `keep` count of
+/// [`Rotation::Size`]. This is checked when the file is opened and every
+/// time it is rotated. Only files named like the rotated ones are ever
+/// deleted, e.g. `logs/app-2025-06-01.txt` or `logs/app.txt.3.gz`. Other
+/// files in the same directory are never touched, even when their names
+/// start the same.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use sir_logger::{LoggerBuilder, Retention, Rotation};
+/// use std::time::Duration;
+///
+/// LoggerBuilder::new()
+///     .log_file("logs/app.txt")
+///     .rotation(Rotation::Daily)
+///     .retention(Retention {
+///         max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+///         max_total_bytes: Some(500 * 1024 * 1024),
+///     })
+///     .init()
+///     .expect("Unable to setup logger");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Retention {
+    /// Delete rotated files that were last written longer ago than this.
+    pub max_age: Option<Duration>,
+    /// Delete the oldest rotated files until all the log files, including
+    /// the current one, take up at most this many bytes.
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Retention {
+    /// Delete whichever of the `rotated` files are too old, or too
+    /// many. Errors are ignored, the files are just tried again next time.
+    fn apply(&self, current: &Path, rotated: Vec<PathBuf>) {
+        if *self == Retention::default() {
+            return;
+        }
+
+        let now = SystemTime::now();
+        let mut files: Vec<(PathBuf, SystemTime, u64)> = rotated
+            .into_iter()
+            .filter_map(|path| {
+                let metadata = fs::metadata(&path).ok()?;
+                Some((path, metadata.modified().ok()?, metadata.len()))
+            })
+            .collect();
+
+        if let Some(max_age) = self.max_age {
+            files.retain(|(path, modified, _)| {
+                let expired = now.duration_since(*modified).is_ok_and(|age| age > max_age);
+                !(expired && fs::remove_file(path).is_ok())
+            });
+        }
+
+        if let Some(max_total_bytes) = self.max_total_bytes {
+            // Keep the newest files, the current file always stays.
+            files.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+            let mut total = fs::metadata(current).map_or(0, |metadata| metadata.len());
+            for (path, _, size) in files {
+                total += size;
+                if total > max_total_bytes {
+                    let _ = fs::remove_file(path);
+                }
+            }
+        }
+    }
+}
+
+/// A log file that gets rotated once it gets too big.
+///
+/// fern writes a single record in several pieces and then flushes, so
+/// everything is held back until `flush` to make sure a record is never
+/// split between two files.
+pub(crate) struct SizeRotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+    compress: bool,
+    compressing: Option<JoinHandle<()>>,
+    retention: Retention,
+    pending: Vec<u8>,
+}
+
+impl SizeRotatingFile {
+    pub(crate) fn new(
+        path: &Path,
+        mode: FileMode,
+        max_bytes: u64,
+        keep: usize,
+        compress: bool,
+        retention: Retention,
+    ) -> io::Result<Self> {
+        let file = open(path, mode)?;
+        let written = file.metadata()?.len();
+        retention.apply(path, size_rotated(path, keep));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            written,
+            max_bytes,
+            keep,
+            compress,
+            compressing: None,
+            retention,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Whether the last rotated file is still being compressed. It has to
+    /// be done before it gets moved, and this runs with fern's lock held so
+    /// it can't be waited on, the rotation is put off until it's finished.
+    fn is_compressing(&mut self) -> bool {
+        match &self.compressing {
+            Some(compressing) if !compressing.is_finished() => true,
+            Some(_) => {
+                let _ = self.compressing.take().map(JoinHandle::join);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Shift all the old files along by one, dropping the oldest, and
+    /// then start a fresh file.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(numbered(&self.path, self.keep));
+            let _ = fs::remove_file(gzipped(&numbered(&self.path, self.keep)));
+
+            for index in (1..self.keep).rev() {
+                let from = numbered(&self.path, index);
+                let to = numbered(&self.path, index + 1);
+
+                if from.exists() {
+                    fs::rename(&from, &to)?;
+                }
+
+                if gzipped(&from).exists() {
+                    fs::rename(gzipped(&from), gzipped(&to))?;
+                }
+            }
+
+            fs::rename(&self.path, numbered(&self.path, 1))?;
+        }
+
+        let (path, keep, retention) = (self.path.clone(), self.keep, self.retention);
+        let compress = (self.compress && keep > 0).then(|| numbered(&path, 1));
+        self.compressing = compress_then(compress, move || retention.apply(&path, size_rotated(&path, keep)));
+
+        self.file = open(&self.path, FileMode::Append)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return self.file.flush();
+        }
+
+        // Only rotate a file with something in it, otherwise a single huge
+        // record would rotate forever. The file can go a little past
+        // `max_bytes` while the last one is still being compressed.
+        let size = self.pending.len() as u64;
+        if self.written > 0 && self.written + size > self.max_bytes && !self.is_compressing() {
+            self.rotate()?;
+        }
+
+        self.file.write_all(&self.pending)?;
+        self.written += size;
+        self.pending.clear();
+        self.file.flush()
+    }
+}
+
+/// Every rotated file of the size rotated log at `path`, compressed or not.
+fn size_rotated(path: &Path, keep: usize) -> Vec<PathBuf> {
+    (1..=keep)
+        .flat_map(|index| {
+            let path = numbered(path, index);
+            [gzipped(&path), path]
+        })
+        .collect()
+}
+
+/// A log file that gets swapped out for a new one every day or hour.
+///
+/// Just like `SizeRotatingFile`, writes are held back until `flush`
+/// so the file is only ever switched between records.
+pub(crate) struct TimeRotatingFile {
+    prefix: OsString,
+    suffix: OsString,
+    hourly: bool,
+    timezone: Timezone,
+    period: String,
+    file: File,
+    compress: bool,
+    compressing: Option<JoinHandle<()>>,
+    retention: Retention,
+    pending: Vec<u8>,
+}
+
+impl TimeRotatingFile {
+    pub(crate) fn new(
+        path: &Path,
+        mode: FileMode,
+        hourly: bool,
+        timezone: Timezone,
+        compress: bool,
+        retention: Retention,
+    ) -> io::Result<Self> {
+        // Split `logs/app.txt` into `logs/app-` and `.txt`.
+        let mut prefix = path.with_extension("").into_os_string();
+        prefix.push("-");
+
+        let mut suffix = OsString::new();
+        if let Some(extension) = path.extension() {
+            suffix.push(".");
+            suffix.push(extension);
+        }
+
+        let period = period(hourly, timezone, SystemTime::now());
+
+        // Unless asked otherwise, starting mid-day appends to that
+        // day's file rather than replacing it.
+        let current = named(&prefix, &period, &suffix);
+        let file = open(&current, mode)?;
+        retention.apply(&current, time_rotated(&prefix, &suffix, &current));
+
+        Ok(Self {
+            prefix,
+            suffix,
+            hourly,
+            timezone,
+            period,
+            file,
+            compress,
+            compressing: None,
+            retention,
+            pending: Vec::new(),
+        })
+    }
+}
+
+/// Every older file of the time rotated log split into `prefix` and
+/// `suffix`, e.g. `logs/app-2025-06-01.txt` and `logs/app-2025-05-31-13.txt.gz`.
+/// Only names with a real date in the middle count, so other files that
+/// happen to start the same, like `logs/app-errors.txt`, are left alone.
+fn time_rotated(prefix: &OsString, suffix: &OsString, current: &Path) -> Vec<PathBuf> {
+    let prefix = Path::new(prefix);
+    let dir = match prefix.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let (Some(start), Some(end)) = (prefix.file_name().and_then(|start| start.to_str()), suffix.to_str()) else {
+        return Vec::new();
+    };
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name() != current.file_name())
+        .filter(|path| {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                return false;
+            };
+
+            let name = name.strip_suffix(".gz").unwrap_or(name);
+            name.strip_prefix(start)
+                .and_then(|name| name.strip_suffix(end))
+                .is_some_and(is_period)
+        })
+        .collect()
+}
+
+/// Whether `period` is a day like `2025-06-01` or an hour like
+/// `2025-06-01-13`, see [`period`].
+fn is_period(period: &str) -> bool {
+    let number = |part: &str, digits: usize, range: std::ops::RangeInclusive<u32>| {
+        part.len() == digits
+            && part.bytes().all(|byte| byte.is_ascii_digit())
+            && part.parse().is_ok_and(|number| range.contains(&number))
+    };
+
+    let parts: Vec<&str> = period.split('-').collect();
+    match parts[..] {
+        [year, month, day] => number(year, 4, 0..=9999) && number(month, 2, 1..=12) && number(day, 2, 1..=31),
+        [year, month, day, hour] => is_period(&[year, month, day].join("-")) && number(hour, 2, 0..=23),
+        _ => false,
+    }
+}
+
+impl Write for TimeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return self.file.flush();
+        }
+
+        let period = period(self.hourly, self.timezone, SystemTime::now());
+        if period != self.period {
+            self.file.flush()?;
+            self.file = open(&named(&self.prefix, &period, &self.suffix), FileMode::Append)?;
+
+            let old = std::mem::replace(&mut self.period, period);
+
+            // Every period has its own name, so there's no need to wait
+            // for the last one to finish compressing.
+            if self.compressing.as_ref().is_some_and(JoinHandle::is_finished) {
+                let _ = self.compressing.take().map(JoinHandle::join);
+            }
+
+            let (prefix, suffix, retention) = (self.prefix.clone(), self.suffix.clone(), self.retention);
+            let current = named(&prefix, &self.period, &suffix);
+            let compress = self.compress.then(|| named(&prefix, &old, &suffix));
+            self.compressing = compress_then(compress, move || {
+                retention.apply(&current, time_rotated(&prefix, &suffix, &current))
+            });
+        }
+
+        self.file.write_all(&self.pending)?;
+        self.pending.clear();
+        self.file.flush()
+    }
+}
+
+type Buffer = Mutex<BufWriter<Box<dyn Write + Send>>>;
+
+/// Every buffered file that is still open, so they can all be flushed
+/// at once by [`flush_buffers`].
+static BUFFERS: Mutex<Vec<Weak<Buffer>>> = Mutex::new(Vec::new());
+
+/// How often the background thread flushes the buffered files.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Holds lines in memory and writes them in big chunks, instead of doing a
+/// write for every line.
+///
+/// fern flushes after every record, so that flush only moves the finished
+/// record into the buffer. The buffer itself is written out every second by
+/// a background thread, when it fills up, or when [`flush_buffers`] is called.
+/// Only whole records are ever written, so records are still never split
+/// between two rotated files. A size rotated file only sees the whole
+/// chunk though, so it can get up to `capacity` bytes too big.
+pub(crate) struct BufferedFile {
+    shared: Arc<Buffer>,
+    record: Vec<u8>,
+}
+
+impl BufferedFile {
+    pub(crate) fn new(inner: Box<dyn Write + Send>, capacity: usize) -> Self {
+        let shared = Arc::new(Mutex::new(BufWriter::with_capacity(capacity, inner)));
+
+        let mut buffers = lock(&BUFFERS);
+        if buffers.is_empty() {
+            start_flushing();
+        }
+        buffers.push(Arc::downgrade(&shared));
+
+        Self {
+            shared,
+            record: Vec::new(),
+        }
+    }
+}
+
+impl Write for BufferedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.record.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = lock(&self.shared).write_all(&self.record);
+        self.record.clear();
+        result
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Write out everything held by the buffered files, this is called by
+/// [`flush`](crate::flush) and the panic hook.
+pub(crate) fn flush_buffers() {
+    let buffers: Vec<Arc<Buffer>> = lock(&BUFFERS).iter().filter_map(Weak::upgrade).collect();
+
+    for buffer in buffers {
+        let _ = lock(&buffer).flush();
+    }
+}
+
+/// Start the thread that flushes the buffered files every second, it
+/// stops once they have all been closed.
+fn start_flushing() {
+    let _ = std::thread::Builder::new()
+        .name(String::from("sir_logger-flush"))
+        .spawn(|| loop {
+            std::thread::sleep(FLUSH_INTERVAL);
+            flush_buffers();
+
+            let mut buffers = lock(&BUFFERS);
+            buffers.retain(|buffer| buffer.strong_count() > 0);
+            if buffers.is_empty() {
+                return;
+            }
+        });
+}
+
+/// The path of a file once it has been compressed, e.g. `log.txt.1.gz`.
+fn gzipped(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Compress `path` to `path.gz` (if there is one to compress) and then
+/// run `cleanup`, on a background thread so logging doesn't have to wait.
+/// The cleanup has to wait, or it could delete the file half way through.
+///
+/// Errors are logged as warnings. This is called with fern's lock on the
+/// log file held, so a thread that can't be started is only reported once
+/// the lock is let go, see [`report_deferred`].
+#[cfg(feature = "gzip")]
+fn compress_then(path: Option<PathBuf>, cleanup: impl FnOnce() + Send + 'static) -> Option<JoinHandle<()>> {
+    let Some(path) = path else {
+        cleanup();
+        return None;
+    };
+
+    let compressing = std::thread::Builder::new()
+        .name(String::from("sir_logger-gzip"))
+        .spawn(move || {
+            if let Err(err) = gzip(&path) {
+                log::warn!(target: "sir_logger", "Unable to compress {}: {err}", path.display());
+            }
+
+            cleanup();
+        });
+
+    match compressing {
+        Ok(compressing) => Some(compressing),
+        Err(err) => {
+            defer_warning(format!("Unable to start compressing log files: {err}"));
+            None
+        }
+    }
+}
+
+/// Warnings from while fern's lock was held, waiting to be logged.
+static DEFERRED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// So logging doesn't have to lock [`DEFERRED`] just to find it empty.
+static HAS_DEFERRED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(feature = "gzip")]
+fn defer_warning(warning: String) {
+    lock(&DEFERRED).push(warning);
+    HAS_DEFERRED.store(true, Ordering::Release);
+}
+
+/// Log the warnings that couldn't be logged straight away. This has to be
+/// called once nothing is held, so the warnings can be written like any
+/// other record.
+pub(crate) fn report_deferred() {
+    if !HAS_DEFERRED.swap(false, Ordering::Acquire) {
+        return;
+    }
+
+    for warning in std::mem::take(&mut *lock(&DEFERRED)) {
+        log::warn!(target: "sir_logger", "{warning}");
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+fn compress_then(_: Option<PathBuf>, cleanup: impl FnOnce()) -> Option<JoinHandle<()>> {
+    cleanup();
+    None
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(path: &Path) -> io::Result<()> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut input = File::open(path)?;
+    let mut output = GzEncoder::new(File::create(gzipped(path))?, Compression::default());
+
+    io::copy(&mut input, &mut output)?;
+    output.finish()?;
+    fs::remove_file(path)
+}
+
+/// The name of the day or hour that `time` falls in, e.g. `2025-06-01`
+/// or `2025-06-01-13`.
+fn period(hourly: bool, timezone: Timezone, time: SystemTime) -> String {
+    #[cfg(feature = "chrono")]
+    if timezone == Timezone::Local {
+        let format = if hourly { "%Y-%m-%d-%H" } else { "%Y-%m-%d" };
+        return chrono::DateTime::<chrono::Local>::from(time)
+            .format(format)
+            .to_string();
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    let _ = timezone;
+
+    // This is always `YYYY-MM-DDTHH:MM:SSZ`.
+    let date = humantime::format_rfc3339_seconds(time).to_string();
+    if hourly {
+        format!("{}-{}", &date[..10], &date[11..13])
+    } else {
+        date[..10].to_string()
+    }
+}
+
+/// Join together the parts of a time rotated file name.
+fn named(prefix: &OsString, period: &str, suffix: &OsString) -> PathBuf {
+    let mut name = prefix.clone();
+    name.push(period);
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Open a log file, creating it (and the directories it's in) if needed.
+pub(crate) fn open(path: &Path, mode: FileMode) -> io::Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut options = OpenOptions::new();
+    options.create(true);
+
+    match mode {
+        FileMode::Append => options.append(true),
+        FileMode::Truncate => options.write(true).truncate(true),
+    };
+
+    options.open(path)
+}
+
+/// Check that a log file could be opened, without changing the file or
+/// leaving anything behind.
+pub(crate) fn check(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        OpenOptions::new().append(true).open(path)?;
+        return Ok(());
+    }
+
+    // The file would be created in the closest directory that exists.
+    let dir = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.as_os_str().is_empty() || dir.exists())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    if !fs::metadata(dir)?.is_dir() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("'{}' isn't a directory", dir.display())));
+    }
+
+    // The permissions of a directory don't say whether files can be created
+    // in it, e.g. the read-only flag on unix is only about the owner, so the
+    // only sure way is to try.
+    let probe = dir.join(format!(".sir_logger_check_{}", std::process::id()));
+    OpenOptions::new().write(true).create_new(true).open(&probe)?;
+    fs::remove_file(probe)
+}
+
+/// The path of the `index`th rotated file, e.g. `log.txt.1`.
+fn numbered(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
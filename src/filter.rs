@@ -0,0 +1,249 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::borrow::Cow;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The level a single target is logged at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TargetLevel {
+    /// Follow the main level, this is used for the root and `high_priority`.
+    Main,
+    /// Always use this level.
+    Fixed(LevelFilter),
+}
+
+/// The levels of every target. fern's levels can't be changed once the
+/// logger is installed, so everything is filtered through this instead.
+#[derive(Debug)]
+pub(crate) struct Filters {
+    level: LevelFilter,
+    external: Option<LevelFilter>,
+    targets: Vec<(Cow<'static, str>, TargetLevel)>,
+    /// Nothing is ever logged above this, whatever the other levels say.
+    ceiling: LevelFilter,
+}
+
+static FILTERS: RwLock<Filters> = RwLock::new(Filters::new(LevelFilter::Off, None));
+
+impl Filters {
+    /// Filter everything at `level`, unless `external` is set, in
+    /// which case that is used for every unknown target.
+    pub(crate) const fn new(level: LevelFilter, external: Option<LevelFilter>) -> Self {
+        Self {
+            level,
+            external,
+            targets: Vec::new(),
+            ceiling: LevelFilter::Trace,
+        }
+    }
+
+    /// Never log anything more verbose than `ceiling`.
+    pub(crate) fn cap(&mut self, ceiling: LevelFilter) {
+        self.ceiling = ceiling;
+    }
+
+    /// Set the level of a target, replacing whatever it was before.
+    pub(crate) fn set(&mut self, target: Cow<'static, str>, level: TargetLevel) {
+        match self.targets.iter_mut().find(|(name, _)| *name == target) {
+            Some((_, old)) => *old = level,
+            None => self.targets.push((target, level)),
+        }
+    }
+
+    /// The level a target is logged at. Just like `RUST_LOG`, the
+    /// most specific target wins, so `app::db` beats `app`.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let mut module = target;
+
+        loop {
+            if let Some((_, level)) = self.targets.iter().find(|(name, _)| name == module) {
+                return self.resolve(*level);
+            }
+
+            match module.rfind("::") {
+                Some(index) => module = &module[..index],
+                None => return self.external.unwrap_or(self.level),
+            }
+        }
+    }
+
+    fn resolve(&self, level: TargetLevel) -> LevelFilter {
+        match level {
+            TargetLevel::Main => self.level,
+            TargetLevel::Fixed(level) => level,
+        }
+    }
+
+    /// Check if a record would make it past the filters.
+    pub(crate) fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target()).min(self.ceiling)
+    }
+
+    /// The most verbose level anything could be logged at, this
+    /// lets the `log` macros skip records early.
+    pub(crate) fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| self.resolve(*level))
+            .chain([self.external.unwrap_or(self.level)])
+            .max()
+            .unwrap_or(LevelFilter::Off)
+            .min(self.ceiling)
+    }
+}
+
+/// Wraps a logger, only passing on the records that `filter` lets through,
+/// see [`LoggerBuilder::filter_record`](crate::LoggerBuilder::filter_record).
+pub(crate) struct RecordFilter {
+    pub(crate) inner: Box<dyn Log>,
+    pub(crate) filter: Arc<RecordFilterFn>,
+}
+
+impl Log for RecordFilter {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.enabled(record.metadata()) && (self.filter)(record) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// A closure that decides if a record should be logged.
+pub(crate) type RecordFilterFn = dyn Fn(&Record) -> bool + Send + Sync;
+
+/// A closure that decides if a target and level should be logged.
+pub(crate) type MetadataFilterFn = dyn Fn(&Metadata) -> bool + Send + Sync;
+
+pub(crate) fn read() -> RwLockReadGuard<'static, Filters> {
+    FILTERS.read().unwrap_or_else(|err| err.into_inner())
+}
+
+fn write() -> RwLockWriteGuard<'static, Filters> {
+    FILTERS.write().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Replace all the filters.
+pub(crate) fn install(filters: Filters) {
+    *write() = filters;
+}
+
+/// Change the main level of the logger while the program is running,
+/// e.g. from an admin command.
+///
+/// This changes the level of the root crate and the `high_priority` crates.
+/// Other crates are changed too, unless
+/// [`LoggerBuilder::external_level`](crate::LoggerBuilder::external_level)
+/// was used. Targets with their own level, from `suppress`, `level_for` or
+/// `RUST_LOG`, are left alone. The per-file levels still apply on top of this.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+///
+/// sir_logger::LoggerBuilder::new()
+///     .level(LevelFilter::Info)
+///     .ring_buffer(10)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// log::trace!("hidden");
+/// sir_logger::set_level(LevelFilter::Trace);
+/// log::trace!("shown");
+///
+/// assert_eq!(sir_logger::recent_logs().len(), 1);
+/// ```
+pub fn set_level(level: LevelFilter) {
+    let mut filters = write();
+    filters.level = level;
+    log::set_max_level(filters.max_level().min(log::STATIC_MAX_LEVEL));
+}
+
+/// Change the level of a single target while the program is running, e.g.
+/// to turn on `trace` for one module while looking into a live issue.
+///
+/// Any target can be changed, whether it had its own level or not. Like with
+/// `RUST_LOG`, changing `my_app::db` also changes `my_app::db::pool`, unless
+/// that has its own level. This takes priority over `suppress`,
+/// `high_priority` and [`set_level`], but not over
+/// [`LoggerBuilder::max_level`](crate::LoggerBuilder::max_level).
+///
+/// This can be called from any thread, records that are being logged at the
+/// same time see either the old level or the new one. Setting the logger up
+/// again throws away the changes.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+///
+/// sir_logger::LoggerBuilder::new()
+///     .level(LevelFilter::Info)
+///     .ring_buffer(10)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// sir_logger::set_level_for("my_app::db", LevelFilter::Trace);
+/// log::trace!(target: "my_app::db::pool", "shown");
+/// log::trace!(target: "my_app::http", "hidden");
+///
+/// assert_eq!(sir_logger::recent_logs().len(), 1);
+/// ```
+pub fn set_level_for(target: &str, level: LevelFilter) {
+    let mut filters = write();
+    filters.set(Cow::Owned(target.to_string()), TargetLevel::Fixed(level));
+    log::set_max_level(filters.max_level().min(log::STATIC_MAX_LEVEL));
+}
+
+/// The main level of the logger, this is the level `setup` picked, or
+/// the last one passed to [`set_level`]. Returns `Off` if the logger isn't
+/// setup yet, or its [`LoggerGuard`](crate::LoggerGuard) has been dropped.
+///
+/// Use [`log::log_enabled!`] to check a specific target instead, this is
+/// useful to skip building expensive debug output.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+///
+/// let guard = sir_logger::LoggerBuilder::new()
+///     .level(LevelFilter::Warn)
+///     .init_scoped()
+///     .expect("Unable to setup logger");
+///
+/// assert_eq!(sir_logger::current_level(), LevelFilter::Warn);
+///
+/// drop(guard);
+/// assert_eq!(sir_logger::current_level(), LevelFilter::Off);
+/// ```
+pub fn current_level() -> LevelFilter {
+    read().level
+}
@@ -0,0 +1,1127 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::context;
+use fern::colors::{Color, ColoredLevelConfig};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt::{self, Write as _};
+use std::hash::{Hash, Hasher};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::OnceLock;
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+use std::time::Instant;
+use std::time::{Duration, SystemTime};
+
+/// When the terminal output should be colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color only when the output is a terminal and `NO_COLOR` isn't set.
+    #[default]
+    Auto,
+    /// Always color, even when the output isn't a terminal or `NO_COLOR` is set.
+    Always,
+    /// Never color, even when the output is a terminal.
+    Never,
+}
+
+/// How the date at the start of each line should be formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timestamp {
+    /// RFC3339 with second precision, e.g. `2025-06-01T12:00:00Z`.
+    #[default]
+    Rfc3339Seconds,
+    /// RFC3339 with millisecond precision, e.g. `2025-06-01T12:00:00.123Z`.
+    Rfc3339Millis,
+    /// RFC3339 with nanosecond precision, e.g. `2025-06-01T12:00:00.123456789Z`.
+    Rfc3339Nanos,
+    /// The time since the logger was setup instead of the date, e.g.
+    /// `+1.234s`. This is handy for seeing how long things take in a
+    /// single run.
+    Elapsed,
+    /// The seconds since the Unix epoch, e.g. `1748779200`. These sort
+    /// and parse easily, and are numbers in [`OutputFormat::Json`].
+    UnixSeconds,
+    /// The milliseconds since the Unix epoch, e.g. `1748779200123`.
+    UnixMillis,
+    /// Any `strftime` style format, e.g. `"%d/%m/%Y %H:%M"` for
+    /// `01/06/2025 12:00`, see [`chrono::format::strftime`] for everything
+    /// it understands. The [`Timezone`] is used as well. A format that
+    /// doesn't parse is printed as it is.
+    ///
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    Custom(&'static str),
+    /// Don't show a date at all, useful when running under something like
+    /// journald or docker that already adds one.
+    ///
+    /// ```rust
+    /// use sir_logger::{LoggerBuilder, Timestamp};
+    ///
+    /// LoggerBuilder::new()
+    ///     .timestamp(Timestamp::None)
+    ///     .ring_buffer(1)
+    ///     .init()
+    ///     .expect("Unable to setup logger");
+    ///
+    /// // Not even the space the date would've been followed by.
+    /// log::info!(target: "app", "no date");
+    /// assert_eq!(sir_logger::recent_logs(), ["[INFO app] no date"]);
+    /// ```
+    None,
+}
+
+/// The layout of each line written to an output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// The human readable `[date LEVEL target] message` layout.
+    #[default]
+    Text,
+    /// One JSON object per line, with the fields `timestamp`, `level`,
+    /// `target`, `message` and `file`/`line` when they are known.
+    /// The output is never colored.
+    Json,
+    /// The `logfmt` layout, e.g. `ts=... level=info target=app msg="hello world"`.
+    /// The output is never colored.
+    Logfmt,
+    /// GELF 1.1 JSON objects for Graylog, with the fields `version`, `host`,
+    /// `short_message`, `timestamp`, `level` as a syslog severity, `_target`
+    /// and `_file`/`_line` when they are known. The output is never colored.
+    Gelf,
+}
+
+/// The color of each level name, e.g. "INFO", and of the date and target.
+/// These are only used when the output is colored, see [`ColorChoice`].
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use sir_logger::{Color, LevelColors, LoggerBuilder};
+///
+/// LoggerBuilder::new()
+///     .level_colors(LevelColors {
+///         error: Color::Magenta,
+///         date: None,
+///         ..LevelColors::default()
+///     })
+///     .init()
+///     .expect("Unable to setup logger");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelColors {
+    pub error: Color,
+    pub warn: Color,
+    pub info: Color,
+    pub debug: Color,
+    pub trace: Color,
+    /// The color of the date, `None` leaves it uncolored.
+    pub date: Option<Color>,
+    /// The color of the target, `None` leaves it uncolored. This is
+    /// ignored when [`hash_target_colors`](crate::LoggerBuilder::hash_target_colors)
+    /// is on.
+    pub target: Option<Color>,
+}
+
+impl Default for LevelColors {
+    fn default() -> Self {
+        Self {
+            error: Color::Red,
+            warn: Color::Yellow,
+            info: Color::Green,
+            debug: Color::White,
+            trace: Color::BrightBlack,
+            date: Some(Color::Blue),
+            target: Some(Color::Green),
+        }
+    }
+}
+
+impl From<LevelColors> for ColoredLevelConfig {
+    fn from(colors: LevelColors) -> Self {
+        ColoredLevelConfig::new()
+            .error(colors.error)
+            .warn(colors.warn)
+            .info(colors.info)
+            .debug(colors.debug)
+            .trace(colors.trace)
+    }
+}
+
+/// The symbol put in front of each level name, see
+/// [`LoggerBuilder::level_symbols`](crate::LoggerBuilder::level_symbols).
+/// The default symbols need a terminal (and font) that can show Unicode.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use sir_logger::{LevelSymbols, LoggerBuilder};
+///
+/// // Plain ASCII, for terminals without Unicode.
+/// LoggerBuilder::new()
+///     .custom_level_symbols(LevelSymbols {
+///         error: "!!",
+///         warn: "!",
+///         info: "*",
+///         debug: "-",
+///         trace: ".",
+///     })
+///     .init()
+///     .expect("Unable to setup logger");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelSymbols {
+    pub error: &'static str,
+    pub warn: &'static str,
+    pub info: &'static str,
+    pub debug: &'static str,
+    pub trace: &'static str,
+}
+
+impl Default for LevelSymbols {
+    fn default() -> Self {
+        Self {
+            error: "\u{2716}",
+            warn: "\u{26a0}",
+            info: "\u{2139}",
+            debug: "\u{25cf}",
+            trace: "\u{00b7}",
+        }
+    }
+}
+
+impl LevelSymbols {
+    fn get(&self, level: log::Level) -> &'static str {
+        match level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug => self.debug,
+            log::Level::Trace => self.trace,
+        }
+    }
+}
+
+/// How the level name is padded so the messages line up, the padding is
+/// never colored.
+///
+/// ## Example
+///
+/// ```rust
+/// use sir_logger::{LevelPadding, LoggerBuilder, Timestamp};
+///
+/// LoggerBuilder::new()
+///     .timestamp(Timestamp::None)
+///     .level_padding(LevelPadding::Left)
+///     .ring_buffer(2)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// log::error!(target: "app", "failed");
+/// log::info!(target: "app", "retrying");
+/// assert_eq!(sir_logger::recent_logs(), ["[ERROR app] failed", "[ INFO app] retrying"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelPadding {
+    /// Don't pad the level, e.g. `[date INFO my_app]`.
+    #[default]
+    None,
+    /// Pad the level with spaces before it, e.g. `[date  INFO my_app]`.
+    Left,
+    /// Pad the level with spaces after it, e.g. `[date INFO  my_app]`.
+    Right,
+}
+
+/// How much of the target is shown in each line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TargetLength {
+    /// Show the whole target, e.g. `my_app::db::pool`.
+    #[default]
+    Full,
+    /// Only show the last few `::` segments, e.g. `db::pool` for 2.
+    Segments(usize),
+    /// Cut the start of the target off so it is at most this many
+    /// characters, including the `…`, e.g. `…db::pool` for 9.
+    Chars(usize),
+}
+
+impl TargetLength {
+    /// Shorten `target`, this only allocates when it is actually cut.
+    fn shorten(self, target: &str) -> Cow<'_, str> {
+        match self {
+            TargetLength::Full => Cow::Borrowed(target),
+            TargetLength::Segments(count) => {
+                let start = target
+                    .rmatch_indices("::")
+                    .nth(count.saturating_sub(1))
+                    .map_or(0, |(index, _)| index + 2);
+
+                Cow::Borrowed(&target[start..])
+            }
+            TargetLength::Chars(max) => {
+                let length = target.chars().count();
+                if length <= max {
+                    return Cow::Borrowed(target);
+                }
+
+                let tail: String = target.chars().skip(length - max.saturating_sub(1)).collect();
+                Cow::Owned(format!("…{tail}"))
+            }
+        }
+    }
+}
+
+/// What to do with messages that have more than one line, e.g.
+/// from `debug!("{:#?}", value)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiLine {
+    /// Write the message as is, so only the first line has the prefix.
+    #[default]
+    Raw,
+    /// Start every line with the `[date LEVEL target]` prefix, so each
+    /// line can be parsed on its own.
+    Prefix,
+    /// Indent the other lines so they line up with the first one.
+    Indent,
+}
+
+/// What to do with control characters in messages, like escape codes or
+/// a stray `\r`, which could mess with the terminal or hide what's in a
+/// file when the message comes from untrusted input. Newlines and tabs are
+/// always kept, see [`MultiLine`] for how newlines are written.
+///
+/// This only changes [`OutputFormat::Text`], the other formats always
+/// escape everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlChars {
+    /// Write them as they are everywhere, so messages can color themselves.
+    Keep,
+    /// Escape them everywhere except stdout and stderr, e.g. `\x1b` for the
+    /// escape character.
+    #[default]
+    EscapeOutsideTerminal,
+    /// Escape them everywhere.
+    Escape,
+}
+
+/// Which timezone the date of each line is shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Timezone {
+    /// Use UTC, shown with a `Z` suffix.
+    #[default]
+    Utc,
+    /// Use the timezone of the machine, shown with the offset, e.g. `+02:00`.
+    /// Requires the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    Local,
+}
+
+/// Anything that can be checked for being a terminal. Only `std` can
+/// implement [`IsTerminal`], this lets the tests pretend to be one.
+pub(crate) trait Terminal {
+    fn is_terminal(&self) -> bool;
+}
+
+impl<T: IsTerminal> Terminal for T {
+    fn is_terminal(&self) -> bool {
+        IsTerminal::is_terminal(self)
+    }
+}
+
+/// Check if output to `stream` should be colored.
+pub(crate) fn should_color(choice: ColorChoice, stream: &impl Terminal) -> bool {
+    // Respect https://no-color.org, an empty value doesn't count.
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+    wants_color(choice, no_color, stream)
+}
+
+/// [`should_color`], with `NO_COLOR` already read.
+fn wants_color(choice: ColorChoice, no_color: bool, stream: &impl Terminal) -> bool {
+    match choice {
+        ColorChoice::Always => {
+            // Still try to make the colors show up properly.
+            #[cfg(windows)]
+            crate::windows::enable_virtual_terminal();
+
+            return true;
+        }
+        ColorChoice::Never => return false,
+        ColorChoice::Auto => {}
+    }
+
+    // Don't fill pipes and files with escape codes.
+    if no_color || !stream.is_terminal() {
+        return false;
+    }
+
+    // Older Windows consoles need to be told to understand escape codes.
+    #[cfg(windows)]
+    return crate::windows::enable_virtual_terminal();
+
+    #[cfg(not(windows))]
+    true
+}
+
+/// A point in time, displayed using the chosen `Timestamp` format.
+struct Date(Timestamp, Timezone, Stamp);
+
+/// When a record was logged, both as a date and as the time since
+/// the logger was setup.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stamp {
+    time: SystemTime,
+    elapsed: Duration,
+}
+
+/// The current time, `SystemTime::now` panics in the browser so
+/// it's asked for the time instead.
+pub(crate) fn now() -> SystemTime {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    return crate::wasm::now();
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    SystemTime::now()
+}
+
+/// When the logger was setup, for [`Timestamp::Elapsed`].
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// `Instant` doesn't work in the browser either, so it's
+/// counted from the current time instead.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+static START: OnceLock<SystemTime> = OnceLock::new();
+
+/// Start counting the time for [`Timestamp::Elapsed`], later calls
+/// keep the first time.
+pub(crate) fn start_clock() {
+    let _ = elapsed();
+}
+
+/// How long it's been since [`start_clock`].
+fn elapsed() -> Duration {
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    return now().duration_since(*START.get_or_init(now)).unwrap_or_default();
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    START.get_or_init(Instant::now).elapsed()
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == Timestamp::Elapsed {
+            let elapsed = self.2.elapsed;
+            return write!(f, "+{}.{:03}s", elapsed.as_secs(), elapsed.subsec_millis());
+        }
+
+        // The epoch is the same in every timezone.
+        let since_epoch = || self.2.time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+        match self.0 {
+            Timestamp::UnixSeconds => return write!(f, "{}", since_epoch().as_secs()),
+            Timestamp::UnixMillis => return write!(f, "{}", since_epoch().as_millis()),
+            _ => {}
+        }
+
+        #[cfg(feature = "chrono")]
+        if let Timestamp::Custom(format) = self.0 {
+            use chrono::format::{Item, StrftimeItems};
+
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return f.write_str(format);
+            }
+
+            return match self.1 {
+                Timezone::Utc => chrono::DateTime::<chrono::Utc>::from(self.2.time).format(format).fmt(f),
+                Timezone::Local => chrono::DateTime::<chrono::Local>::from(self.2.time).format(format).fmt(f),
+            };
+        }
+
+        #[cfg(feature = "chrono")]
+        if self.1 == Timezone::Local {
+            let precision = match self.0 {
+                Timestamp::Rfc3339Seconds => chrono::SecondsFormat::Secs,
+                Timestamp::Rfc3339Millis => chrono::SecondsFormat::Millis,
+                Timestamp::Rfc3339Nanos => chrono::SecondsFormat::Nanos,
+                _ => return Ok(()),
+            };
+
+            let local = chrono::DateTime::<chrono::Local>::from(self.2.time);
+            return f.write_str(&local.to_rfc3339_opts(precision, false));
+        }
+
+        match self.0 {
+            Timestamp::Rfc3339Seconds => humantime::format_rfc3339_seconds(self.2.time).fmt(f),
+            Timestamp::Rfc3339Millis => humantime::format_rfc3339_millis(self.2.time).fmt(f),
+            Timestamp::Rfc3339Nanos => humantime::format_rfc3339_nanos(self.2.time).fmt(f),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Everything that affects how a line is laid out.
+#[derive(Clone, Copy)]
+pub(crate) struct Layout {
+    pub(crate) colors_level: ColoredLevelConfig,
+    pub(crate) date_color: Option<Color>,
+    pub(crate) target_color: Option<Color>,
+    pub(crate) timestamp: Timestamp,
+    pub(crate) timezone: Timezone,
+    pub(crate) source_location: bool,
+    pub(crate) thread: bool,
+    pub(crate) level_padding: LevelPadding,
+    pub(crate) level_symbols: Option<LevelSymbols>,
+    pub(crate) target: bool,
+    pub(crate) target_length: TargetLength,
+    pub(crate) hash_target_colors: bool,
+    pub(crate) full_line_color: bool,
+    pub(crate) hyperlinks: bool,
+    pub(crate) multi_line: MultiLine,
+    pub(crate) max_message_len: Option<usize>,
+    pub(crate) escape_control: bool,
+}
+
+/// Create the formatter for a single output, if `colored` is false then no
+/// escape codes will be written at all.
+pub(crate) fn format_line(
+    layout: Layout,
+    colored: bool,
+) -> impl Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static {
+    move |out, message, record| {
+        let capped = truncate(message, layout.max_message_len);
+        let message: &dyn fmt::Display = match &capped {
+            Some(capped) => capped,
+            None => message,
+        };
+
+        let escaped = layout.escape_control.then(|| escape_control(message)).flatten();
+        let message: &dyn fmt::Display = match &escaped {
+            Some(escaped) => escaped,
+            None => message,
+        };
+
+        if record.target() == crate::RAW_TARGET {
+            out.finish(format_args!("{message}"));
+            return;
+        }
+
+        let mut prefix = String::new();
+        let level_color = layout.colors_level.get_color(&record.level());
+
+        // When the whole line is colored the parts can't have their
+        // own colors, or they'd end the line color early.
+        let escapes = colored;
+        let line_colored = colored && layout.full_line_color;
+        let colored = colored && !layout.full_line_color;
+
+        // The date and its separating space are left out entirely
+        // when timestamps are disabled.
+        if layout.timestamp != Timestamp::None {
+            let date = Date(layout.timestamp, layout.timezone, stamp());
+            match layout.date_color {
+                Some(color) => paint(&mut prefix, colored, color.to_fg_str(), date),
+                None => paint(&mut prefix, false, "", date),
+            }
+            prefix.push(' ');
+        }
+
+        if let Some(symbols) = layout.level_symbols {
+            paint(&mut prefix, colored, level_color.to_fg_str(), symbols.get(record.level()));
+            prefix.push(' ');
+        }
+
+        // The padding goes outside of the escape codes, so the
+        // columns line up by what is actually visible.
+        let padding = " ".repeat(5 - record.level().as_str().len());
+        if layout.level_padding == LevelPadding::Left {
+            prefix.push_str(&padding);
+        }
+
+        if colored {
+            paint(&mut prefix, colored, level_color.to_fg_str(), layout.colors_level.color(record.level()));
+        } else {
+            prefix.push_str(record.level().as_str());
+        }
+
+        if layout.level_padding == LevelPadding::Right {
+            prefix.push_str(&padding);
+        }
+
+        if layout.target {
+            prefix.push(' ');
+            let target = layout.target_length.shorten(record.target());
+            match layout.target_color {
+                _ if layout.hash_target_colors => paint(&mut prefix, colored, hashed_color(record.target()), target),
+                Some(color) => paint(&mut prefix, colored, color.to_fg_str(), target),
+                None => paint(&mut prefix, false, "", target),
+            }
+        }
+
+        if layout.thread {
+            prefix.push(' ');
+            prefix.push_str(&thread_name());
+        }
+
+        // Just leave out whatever isn't known, e.g. records from
+        // some `log` adapters have no location at all.
+        if layout.source_location {
+            if let Some(file) = record.file() {
+                let mut location = String::from(file);
+                if let Some(number) = record.line() {
+                    let _ = write!(location, ":{number}");
+                }
+
+                prefix.push(' ');
+                if escapes && layout.hyperlinks {
+                    hyperlink(&mut prefix, file, &location);
+                } else {
+                    prefix.push_str(&location);
+                }
+            }
+        }
+
+        let split = match layout.multi_line {
+            MultiLine::Raw => None,
+            mode => split_lines(&prefix, message, mode),
+        };
+
+        let message: &dyn fmt::Display = match &split {
+            Some(split) => split,
+            None => message,
+        };
+
+        let mut fields = String::new();
+        for (key, value) in context::fields() {
+            let _ = write!(fields, " {key}=");
+            logfmt_value(&mut fields, value);
+        }
+
+        if line_colored {
+            out.finish(format_args!("\x1B[{}m[{prefix}] {message}{fields}\x1B[0m", level_color.to_fg_str()));
+        } else {
+            out.finish(format_args!("[{prefix}] {message}{fields}"));
+        }
+    }
+}
+
+/// Cut `message` down to at most `max` bytes, plus the marker. Returns
+/// `None` when the message is short enough, or there's no limit.
+fn truncate(message: &fmt::Arguments, max: Option<usize>) -> Option<String> {
+    let max = max?;
+
+    // Formatting is stopped as soon as the message is too long, so
+    // a huge message doesn't get formatted just to be thrown away.
+    let mut capped = Capped {
+        text: String::new(),
+        max,
+    };
+
+    if fmt::write(&mut capped, *message).is_ok() {
+        return None;
+    }
+
+    capped.text.push_str("…(truncated)");
+    Some(capped.text)
+}
+
+/// Escape the control characters in `message`, other than newlines
+/// and tabs. Returns `None` when there aren't any.
+fn escape_control(message: &dyn fmt::Display) -> Option<String> {
+    let message = message.to_string();
+    let is_control = |c: char| c.is_control() && c != '\n' && c != '\t';
+    if !message.contains(is_control) {
+        return None;
+    }
+
+    let mut escaped = String::with_capacity(message.len() + 8);
+    for c in message.chars() {
+        match c {
+            '\r' => escaped.push_str("\\r"),
+            c if is_control(c) && (c as u32) < 0x80 => {
+                let _ = write!(escaped, "\\x{:02x}", c as u32);
+            }
+            c if is_control(c) => {
+                let _ = write!(escaped, "\\u{{{:x}}}", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    Some(escaped)
+}
+
+/// Keeps up to `max` bytes of whatever is written to it, and then errors.
+struct Capped {
+    text: String,
+    max: usize,
+}
+
+impl fmt::Write for Capped {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = self.max - self.text.len();
+        if s.len() <= room {
+            self.text.push_str(s);
+            return Ok(());
+        }
+
+        // Don't cut a character in half.
+        let mut end = room;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        self.text.push_str(&s[..end]);
+        Err(fmt::Error)
+    }
+}
+
+/// Rewrite a message with more than one line, so every line either
+/// starts with `[prefix]` or is lined up under the first.
+/// Returns `None` for messages that fit on one line.
+fn split_lines(prefix: &str, message: &dyn fmt::Display, mode: MultiLine) -> Option<String> {
+    let message = message.to_string();
+    if !message.contains('\n') {
+        return None;
+    }
+
+    let separator = match mode {
+        MultiLine::Prefix => format!("\n[{prefix}] "),
+        // Line up with the message, the `[`, `] ` are 3 more characters.
+        _ => format!("\n{}", " ".repeat(visible_width(prefix) + 3)),
+    };
+
+    Some(message.lines().collect::<Vec<_>>().join(&separator))
+}
+
+/// How many characters of `text` actually show up, skipping escape codes.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1B' {
+            width += 1;
+            continue;
+        }
+
+        match chars.next() {
+            // Colors, e.g. `\x1B[32m`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            // Hyperlinks, ended by `\x1B\\`.
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x1B' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    width
+}
+
+thread_local! {
+    /// The thread a record really came from, when it is being
+    /// written by a different thread.
+    static LOGGED_FROM: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    /// When the record being written was really logged, so the
+    /// background thread shows the right time.
+    static LOGGED_AT: Cell<Option<Stamp>> = const { Cell::new(None) };
+}
+
+/// When the current record was logged.
+pub(crate) fn stamp() -> Stamp {
+    LOGGED_AT.with(Cell::get).unwrap_or_else(|| Stamp {
+        time: now(),
+        elapsed: elapsed(),
+    })
+}
+
+/// Run `log`, with every record treated as if it was logged at `stamp`.
+#[cfg(feature = "async")]
+pub(crate) fn logged_at<R>(stamp: Stamp, log: impl FnOnce() -> R) -> R {
+    let previous = LOGGED_AT.with(|at| at.replace(Some(stamp)));
+    let result = log();
+    LOGGED_AT.with(|at| at.set(previous));
+    result
+}
+
+/// The name of the thread that logged the current record. Unnamed threads
+/// fall back to their id, e.g. `ThreadId(3)`.
+pub(crate) fn thread_name() -> String {
+    if let Some(name) = LOGGED_FROM.with(|from| from.borrow().clone()) {
+        return name;
+    }
+
+    let thread = std::thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+/// Run `log`, with every record treated as if it came from `thread`.
+#[cfg(feature = "async")]
+pub(crate) fn logged_from<R>(thread: String, log: impl FnOnce() -> R) -> R {
+    let previous = LOGGED_FROM.with(|from| from.replace(Some(thread)));
+    let result = log();
+    LOGGED_FROM.with(|from| *from.borrow_mut() = previous);
+    result
+}
+
+/// Create a formatter that writes each record as a single JSON object.
+pub(crate) fn format_json(
+    layout: Layout,
+) -> impl Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static {
+    move |out, message, record| {
+        let capped = truncate(message, layout.max_message_len);
+        let message: &dyn fmt::Display = match &capped {
+            Some(capped) => capped,
+            None => message,
+        };
+
+        let mut line = String::from("{");
+
+        if layout.timestamp != Timestamp::None {
+            line.push_str("\"timestamp\":");
+            let date = Date(layout.timestamp, layout.timezone, stamp());
+            match layout.timestamp {
+                Timestamp::UnixSeconds | Timestamp::UnixMillis => {
+                    let _ = write!(line, "{date}");
+                }
+                _ => json_string(&mut line, date),
+            }
+            line.push(',');
+        }
+
+        line.push_str("\"level\":");
+        json_string(&mut line, record.level());
+        line.push_str(",\"target\":");
+        json_string(&mut line, record.target());
+        line.push_str(",\"message\":");
+        json_string(&mut line, message);
+
+        if let Some(file) = record.file() {
+            line.push_str(",\"file\":");
+            json_string(&mut line, file);
+        }
+
+        if let Some(number) = record.line() {
+            let _ = write!(line, ",\"line\":{number}");
+        }
+
+        for (key, value) in context::fields() {
+            line.push(',');
+            json_string(&mut line, key);
+            line.push(':');
+            json_string(&mut line, value);
+        }
+
+        line.push('}');
+        out.finish(format_args!("{line}"));
+    }
+}
+
+/// Create a formatter that writes each record as a GELF 1.1 object.
+pub(crate) fn format_gelf(
+    layout: Layout,
+) -> impl Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static {
+    let host = hostname();
+
+    move |out, message, record| {
+        let capped = truncate(message, layout.max_message_len);
+        let message: &dyn fmt::Display = match &capped {
+            Some(capped) => capped,
+            None => message,
+        };
+
+        let mut line = String::from("{\"version\":\"1.1\",\"host\":");
+        json_string(&mut line, &host);
+        line.push_str(",\"short_message\":");
+        json_string(&mut line, message);
+
+        // GELF wants the seconds since the epoch, with the
+        // milliseconds after the dot.
+        if layout.timestamp != Timestamp::None {
+            let since_epoch = stamp()
+                .time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default();
+
+            let _ = write!(
+                line,
+                ",\"timestamp\":{}.{:03}",
+                since_epoch.as_secs(),
+                since_epoch.subsec_millis()
+            );
+        }
+
+        let _ = write!(line, ",\"level\":{}", severity(record.level()));
+        line.push_str(",\"_target\":");
+        json_string(&mut line, record.target());
+
+        if let Some(file) = record.file() {
+            line.push_str(",\"_file\":");
+            json_string(&mut line, file);
+        }
+
+        if let Some(number) = record.line() {
+            let _ = write!(line, ",\"_line\":{number}");
+        }
+
+        // Extra GELF fields have to start with an underscore.
+        for (key, value) in context::fields() {
+            line.push(',');
+            json_string(&mut line, format_args!("_{key}"));
+            line.push(':');
+            json_string(&mut line, value);
+        }
+
+        line.push('}');
+        out.finish(format_args!("{line}"));
+    }
+}
+
+/// The syslog severity of a level, this is the GELF `level` and what
+/// `journalctl -p` filters on.
+pub(crate) fn severity(level: log::Level) -> u8 {
+    match level {
+        log::Level::Error => 3,
+        log::Level::Warn => 4,
+        log::Level::Info => 6,
+        log::Level::Debug | log::Level::Trace => 7,
+    }
+}
+
+/// The name of this machine, std doesn't have a way to get it so
+/// this has to make do with what the OS leaves lying around.
+fn hostname() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("localhost"))
+}
+
+/// Create a formatter that writes each record as `logfmt` key/value pairs.
+pub(crate) fn format_logfmt(
+    layout: Layout,
+) -> impl Fn(fern::FormatCallback, &fmt::Arguments, &log::Record) + Sync + Send + 'static {
+    move |out, message, record| {
+        let capped = truncate(message, layout.max_message_len);
+        let message: &dyn fmt::Display = match &capped {
+            Some(capped) => capped,
+            None => message,
+        };
+
+        let mut line = String::new();
+
+        if layout.timestamp != Timestamp::None {
+            line.push_str("ts=");
+            logfmt_value(&mut line, Date(layout.timestamp, layout.timezone, stamp()));
+            line.push(' ');
+        }
+
+        line.push_str("level=");
+        line.push_str(&record.level().as_str().to_lowercase());
+        line.push_str(" target=");
+        logfmt_value(&mut line, record.target());
+        line.push_str(" msg=");
+        logfmt_value(&mut line, message);
+
+        for (key, value) in context::fields() {
+            let _ = write!(line, " {key}=");
+            logfmt_value(&mut line, value);
+        }
+
+        out.finish(format_args!("{line}"));
+    }
+}
+
+/// Write `value` to `line`, quoting and escaping it if it contains anything
+/// that would confuse a `logfmt` parser.
+fn logfmt_value(line: &mut String, value: impl fmt::Display) {
+    let value = value.to_string();
+
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c == '\\' || c.is_control());
+
+    if !needs_quotes {
+        line.push_str(&value);
+        return;
+    }
+
+    // JSON string escaping is a superset of what logfmt needs.
+    json_string(line, value);
+}
+
+/// Write `value` to `line` as a quoted and escaped JSON string.
+pub(crate) fn json_string(line: &mut String, value: impl fmt::Display) {
+    line.push('"');
+    // Writing to a `String` can't fail.
+    let _ = write!(JsonEscape(line), "{value}");
+    line.push('"');
+}
+
+/// Escapes everything written to it so it can be put in a JSON string.
+struct JsonEscape<'a>(&'a mut String);
+
+impl fmt::Write for JsonEscape<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.push_str("\\\""),
+                '\\' => self.0.push_str("\\\\"),
+                '\n' => self.0.push_str("\\n"),
+                '\r' => self.0.push_str("\\r"),
+                '\t' => self.0.push_str("\\t"),
+                c if c.is_control() => write!(self.0, "\\u{:04x}", c as u32)?,
+                c => self.0.push(c),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Write `text` to `line` as an OSC 8 hyperlink to `file`, terminals
+/// that don't understand it will just show the text.
+fn hyperlink(line: &mut String, file: &str, text: &str) {
+    let path = Path::new(file);
+
+    // `file!()` is usually relative to where cargo was run.
+    let path = match std::env::current_dir() {
+        Ok(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    };
+
+    let _ = write!(line, "\x1B]8;;{}\x1B\\{text}\x1B]8;;\x1B\\", file_uri(&path));
+}
+
+/// The `file://` URI of an absolute `path`, with everything that isn't
+/// allowed in a URI percent-encoded, e.g. spaces and `#`.
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    // Windows paths look like `C:\dir`, which is `file:///C:/dir` as a URI.
+    #[cfg(windows)]
+    let path = path.replace('\\', "/");
+
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(char::from(byte))
+            }
+            _ => {
+                let _ = write!(uri, "%{byte:02X}");
+            }
+        }
+    }
+
+    uri
+}
+
+/// Pick a color for `target`, the same target always gets the same color.
+fn hashed_color(target: &str) -> &'static str {
+    // Every foreground color except black, white and their bright
+    // versions, so the text is readable on both light and dark terminals.
+    const PALETTE: [&str; 12] = [
+        "31", "32", "33", "34", "35", "36", "91", "92", "93", "94", "95", "96",
+    ];
+
+    let mut hasher = DefaultHasher::new();
+    target.hash(&mut hasher);
+    PALETTE[(hasher.finish() % PALETTE.len() as u64) as usize]
+}
+
+/// Write `value` to `line`, wrapped in the escape code for `color`
+/// if the output is `colored`.
+fn paint(line: &mut String, colored: bool, color: &str, value: impl fmt::Display) {
+    // Writing to a `String` can't fail.
+    let _ = if colored {
+        write!(line, "\x1B[{color}m{value}\x1B[0m")
+    } else {
+        write!(line, "{value}")
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fake(bool);
+
+    impl Terminal for Fake {
+        fn is_terminal(&self) -> bool {
+            self.0
+        }
+    }
+
+    #[test]
+    fn auto_colors_only_terminals() {
+        assert!(wants_color(ColorChoice::Auto, false, &Fake(true)));
+        assert!(!wants_color(ColorChoice::Auto, false, &Fake(false)));
+    }
+
+    #[test]
+    fn auto_respects_no_color() {
+        assert!(!wants_color(ColorChoice::Auto, true, &Fake(true)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn file_uris_are_encoded() {
+        let uri = file_uri(Path::new("/home/me/my project/src/#1 100%/é.rs"));
+        assert_eq!(uri, "file:///home/me/my%20project/src/%231%20100%25/%C3%A9.rs");
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn file_uris_have_the_drive() {
+        let uri = file_uri(Path::new(r"C:\Users\me\my project\main.rs"));
+        assert_eq!(uri, "file:///C:/Users/me/my%20project/main.rs");
+    }
+
+    #[test]
+    fn choices_override_the_terminal() {
+        assert!(wants_color(ColorChoice::Always, true, &Fake(false)));
+        assert!(!wants_color(ColorChoice::Never, false, &Fake(true)));
+    }
+}
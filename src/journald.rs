@@ -0,0 +1,86 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::format::severity;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+
+/// Where journald listens for the native protocol.
+const SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Sends records straight to journald, keeping the level and
+/// location as their own fields.
+pub(crate) struct Journald {
+    socket: UnixDatagram,
+    identifier: String,
+}
+
+impl Journald {
+    /// Connect to the journal, `identifier` is shown as the name of the program.
+    pub(crate) fn connect(identifier: String) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(SOCKET)?;
+
+        Ok(Self { socket, identifier })
+    }
+
+    /// Send a single record, errors are ignored as there's nowhere
+    /// left to report them.
+    pub(crate) fn send(&self, record: &log::Record) {
+        let mut entry = Vec::new();
+
+        field(&mut entry, "PRIORITY", severity(record.level()));
+        field(&mut entry, "MESSAGE", record.args());
+        field(&mut entry, "SYSLOG_IDENTIFIER", &self.identifier);
+        field(&mut entry, "TARGET", record.target());
+
+        if let Some(file) = record.file() {
+            field(&mut entry, "CODE_FILE", file);
+        }
+
+        if let Some(line) = record.line() {
+            field(&mut entry, "CODE_LINE", line);
+        }
+
+        if let Some(module) = record.module_path() {
+            field(&mut entry, "CODE_MODULE", module);
+        }
+
+        let _ = self.socket.send(&entry);
+    }
+}
+
+/// Add a field to `entry`, values with a newline have to be sent with their
+/// length instead, see <https://systemd.io/JOURNAL_NATIVE_PROTOCOL>.
+fn field(entry: &mut Vec<u8>, name: &str, value: impl std::fmt::Display) {
+    let value = value.to_string();
+
+    entry.extend_from_slice(name.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
@@ -1,7 +1,59 @@
-///
-/// The `sir_logger` crate is a simple, logging crate designed for debugging
-/// and testing. All documentation is in the `setup` function.
-///
+//!
+//! The `sir_logger` crate is a simple, logging crate designed for debugging
+//! and testing. All documentation is in the `setup` function
+//! and the `LoggerBuilder`.
+//!
+//! With the `tracing` feature, events from crates that use `tracing`
+//! instead of `log` are shown too. The first time the logger is setup it
+//! installs a `tracing` subscriber, unless the program already has one.
+//! Events go through the same levels, `suppress` and `high_priority` as
+//! everything else, using the target of the event, which is its module
+//! unless it was given one. Their fields are added after the message as
+//! `key=value`. Spans aren't shown, only the events inside them.
+//!
+//! The `no-debug-release` feature removes `debug!` and `trace!` calls from
+//! release builds entirely, using `log`'s `release_max_level_info` feature.
+//! The check is a constant in the `log` macros, so the arguments are never
+//! even formatted, and no level set at runtime can bring them back. This
+//! applies to every crate that uses `log`. Only one `release_max_level_*`
+//! feature can be enabled, so enable `log`'s directly for other levels.
+//! Without it, the arguments of records below the level are still skipped,
+//! but only after checking the level at runtime.
+//!
+//! ```rust
+//! use log::LevelFilter;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! static CALLS: AtomicUsize = AtomicUsize::new(0);
+//!
+//! fn expensive() -> usize {
+//!     CALLS.fetch_add(1, Ordering::Relaxed)
+//! }
+//!
+//! sir_logger::LoggerBuilder::new()
+//!     .stdout(false)
+//!     .level(LevelFilter::Info)
+//!     .init()
+//!     .expect("Unable to setup logger");
+//!
+//! log::debug!("{}", expensive());
+//! assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+//!
+//! // Compiled out records stay out, whatever the level.
+//! sir_logger::set_level(LevelFilter::Trace);
+//! log::debug!("{}", expensive());
+//! let compiled_out = log::STATIC_MAX_LEVEL < LevelFilter::Debug;
+//! assert_eq!(CALLS.load(Ordering::Relaxed), if compiled_out { 0 } else { 1 });
+//! ```
+//!
+//! With the `wasm` feature on `wasm32` (e.g. in the browser), everything
+//! that would go to stdout is written to the browser console instead, and
+//! the timestamps come from the browser. Log files are skipped, and
+//! `exit_on_panic` is ignored as there's no process to exit. Threads and
+//! `Instant` don't work there either, so leave out `background` and `dedup`. Use
+//! [`LoggerBuilder::on_record`] or [`LoggerBuilder::writer`] to send the
+//! logs anywhere else.
+//!
 //
 // `sir_logger` - A simple logging library for rust
 // 
@@ -23,16 +75,200 @@
 // USA
 
 
-use fern::colors::{Color, ColoredLevelConfig};
-use log::{LevelFilter, debug, error};
-use std::cell::OnceCell;
+#[cfg(feature = "async")]
+mod background;
+mod builder;
+#[cfg(feature = "config-file")]
+mod config;
+#[cfg(feature = "test-util")]
+mod capture;
+mod dedup;
+mod context;
+mod env;
+#[cfg(all(windows, feature = "eventlog"))]
+mod eventlog;
+mod file;
+mod filter;
+mod format;
+#[cfg(all(target_os = "linux", feature = "journald"))]
+mod journald;
+mod metrics;
+#[cfg(feature = "network")]
+mod network;
+#[cfg(all(feature = "panic-hook", not(feature = "no-panic-handler")))]
+mod panic_hook;
+/// Everything needed to setup the logger and log, in one import.
+///
+/// ```rust
+/// use sir_logger::prelude::*;
+///
+/// LoggerBuilder::new()
+///     .level(LevelFilter::Debug)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// info!("hello");
+/// ```
+pub mod prelude;
+mod recent;
+#[cfg(feature = "tracing")]
+mod subscriber;
+mod switch;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod wasm;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(feature = "async")]
+pub use background::Overflow;
+pub use builder::{FormatFn, LoggerBuilder};
+pub use fern::colors::Color;
+#[cfg(feature = "test-util")]
+pub use capture::{capture, Capture, CapturedRecord};
+pub use context::{remove_context, set_context, with_context, ContextGuard};
+pub use env::parse_level;
+pub use file::{FileMode, LogFile, Retention, Rotation};
+pub use filter::{current_level, set_level, set_level_for};
+pub use format::{ColorChoice, ControlChars, LevelColors, LevelPadding, LevelSymbols, MultiLine, OutputFormat, TargetLength, Timestamp, Timezone};
+pub use metrics::{level_counts, LevelCounts};
+#[cfg(feature = "network")]
+pub use network::Transport;
+pub use recent::recent_logs;
+pub use switch::LoggerGuard;
+#[cfg(all(unix, feature = "syslog"))]
+pub use syslog::Facility;
+
+use std::fmt;
 use std::path::Path;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once `setup` has claimed the right to install the logger, this is
+/// shared between all threads so only one call can ever win.
+pub(crate) static PREVENT_MULTI_INIT: AtomicBool = AtomicBool::new(false);
+
+/// Records logged with this target are written as just the message, with
+/// no date, level or colors, e.g. for output meant for the user that
+/// should still end up in the log file. They're logged at the main level,
+/// like the root crate.
+///
+/// This only changes [`OutputFormat::Text`], the other formats still have
+/// all the fields, with this as the target.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::LoggerBuilder::new()
+///     .root(env!("CARGO_CRATE_NAME"))
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// log::info!("Downloading...");
+/// log::info!(target: sir_logger::RAW_TARGET, "3 files downloaded");
+/// ```
+pub const RAW_TARGET: &str = "sir_logger::raw";
+
+/// The reasons `setup` can fail.
+#[derive(Debug)]
+pub enum SetupError {
+    /// `setup` has already been called once.
+    AlreadyInitialized,
+    /// The log file at `path` could not be opened, e.g. because it's
+    /// a directory or isn't writable.
+    LogFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    /// The logger couldn't be installed, usually because another
+    /// crate has already set a global logger.
+    Apply(log::SetLoggerError),
+    /// A crate name passed to `suppress`, `high_priority` or one of the
+    /// level options was empty (or only whitespace). Only `validate`
+    /// checks for this.
+    EmptyTarget,
+    /// The connection to syslog couldn't be made.
+    #[cfg(all(unix, feature = "syslog"))]
+    Syslog(syslog::Error),
+    /// The connection to journald couldn't be made.
+    #[cfg(all(target_os = "linux", feature = "journald"))]
+    Journald(std::io::Error),
+    /// The event source couldn't be opened.
+    #[cfg(all(windows, feature = "eventlog"))]
+    EventLog(std::io::Error),
+    /// The connection to a remote collector couldn't be made.
+    #[cfg(feature = "network")]
+    Network(std::io::Error),
+    /// The thread for `background` or `dedup` couldn't be started.
+    Thread(std::io::Error),
+    /// The config file at `path` couldn't be read, or has a mistake in it.
+    #[cfg(feature = "config-file")]
+    Config {
+        path: std::path::PathBuf,
+        message: String,
+    },
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupError::AlreadyInitialized => {
+                write!(f, "the logger has already been initialized")
+            }
+            SetupError::LogFile { path, source } => {
+                write!(f, "unable to open log file '{}': {source}", path.display())
+            }
+            SetupError::Apply(err) => write!(f, "unable to install logger: {err}"),
+            SetupError::EmptyTarget => write!(f, "a crate name is empty"),
+            #[cfg(all(unix, feature = "syslog"))]
+            SetupError::Syslog(err) => write!(f, "unable to connect to syslog: {err}"),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            SetupError::Journald(err) => write!(f, "unable to connect to journald: {err}"),
+            #[cfg(all(windows, feature = "eventlog"))]
+            SetupError::EventLog(err) => write!(f, "unable to open the event log: {err}"),
+            #[cfg(feature = "network")]
+            SetupError::Network(err) => write!(f, "unable to connect to the collector: {err}"),
+            SetupError::Thread(err) => write!(f, "unable to start the logging thread: {err}"),
+            #[cfg(feature = "config-file")]
+            SetupError::Config { path, message } => {
+                write!(f, "invalid config file '{}': {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SetupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SetupError::AlreadyInitialized => None,
+            SetupError::LogFile { source, .. } => Some(source),
+            SetupError::Apply(err) => Some(err),
+            SetupError::EmptyTarget => None,
+            #[cfg(all(unix, feature = "syslog"))]
+            SetupError::Syslog(err) => Some(err),
+            #[cfg(all(target_os = "linux", feature = "journald"))]
+            SetupError::Journald(err) => Some(err),
+            #[cfg(all(windows, feature = "eventlog"))]
+            SetupError::EventLog(err) => Some(err),
+            #[cfg(feature = "network")]
+            SetupError::Network(err) => Some(err),
+            SetupError::Thread(err) => Some(err),
+            #[cfg(feature = "config-file")]
+            SetupError::Config { .. } => None,
+        }
+    }
+}
 
-#[cfg(not(feature = "no-panic-handler"))]
-use std::panic;
+impl From<log::SetLoggerError> for SetupError {
+    fn from(err: log::SetLoggerError) -> Self {
+        SetupError::Apply(err)
+    }
+}
 
-const PREVENT_MULTI_INIT: OnceCell<()> = OnceCell::new();
+#[cfg(all(unix, feature = "syslog"))]
+impl From<syslog::Error> for SetupError {
+    fn from(err: syslog::Error) -> Self {
+        SetupError::Syslog(err)
+    }
+}
 
 /// Setup the logger, you should only run this
 /// function **once**.
@@ -40,18 +276,40 @@ const PREVENT_MULTI_INIT: OnceCell<()> = OnceCell::new();
 /// If `level_override` is `Some(_)`, then the environment variable
 /// `RUST_LOG` will be ignored.
 ///
-/// The specified log level will only apply to other crates if one
-/// of `trace`, `error` or `off`. Unless overridden `warn`
-/// is the default for external crates.
-/// This behaviour can be overridden by using the `suppress` or
-/// `high_priority` parameters.
+/// The specified log level applies to every crate, use
+/// [`LoggerBuilder::external_level`] to make other crates quieter.
+/// The level of specific crates can be overridden by using the
+/// `suppress` or `high_priority` parameters.
+///
+/// `suppress` and `high_priority` can be arrays, `Vec`s or any other
+/// iterator, so they can be built at runtime. Use [`LoggerBuilder`] if
+/// the names are `String`s, e.g. from a config file.
 ///
 /// 9/10 times, root should be the output of `env!("CARGO_PKG_NAME")`,
 /// if using workspaces, put the names of extra crates into `high_priority`
+///
+/// This is a shorthand for [`LoggerBuilder`], which is more readable and
+/// has a few more options.
+///
+/// Coloring is disabled when stdout isn't a terminal, or when the `NO_COLOR`
+/// environment variable is set to a non-empty value, see <https://no-color.org>.
+/// Log files are never colored. Use [`LoggerBuilder::color`] to force colors
+/// on or off.
+///
+/// The `log_file` is borrowed so that `None` works without naming a type,
+/// pass `Some(&"path/to/log.txt")` or `Some(&path)` for a `PathBuf`, or use
+/// [`LoggerBuilder::log_file`] to hand over an owned path.
+///
+/// Returns a [`SetupError`] if the logger was already initialized, the
+/// log file couldn't be opened or another logger is already installed.
+/// It is safe to race calls to `setup` from multiple threads, only one
+/// of them will succeed.
 /// 
 /// ## Example
 /// 
-/// ```rust
+/// ```rust,no_run
+/// use log::LevelFilter;
+///
 /// fn main() {
 ///     sir_logger::setup(
 ///         // The log filter override, if `Some(value)`,
@@ -68,133 +326,230 @@ const PREVENT_MULTI_INIT: OnceCell<()> = OnceCell::new();
 ///         ["super_important_crate"],
 /// 
 ///         // A path to a file to store logs, or `None`
-///         Some("path/to/log.txt"),
+///         Some(&"path/to/log.txt"),
 /// 
 ///         // The name of this executable, this'll help the library
 ///         // set the correct log level for all crates.
 ///         env!("CARGO_PKG_NAME")
-///     );
+///     ).expect("Unable to setup logger");
 /// }
 /// 
 /// ```
-pub fn setup<const S: usize, const H: usize>(
+///
+/// ## Calling `setup` twice
+///
+/// ```rust
+/// let handles: Vec<_> = (0..2)
+///     .map(|_| std::thread::spawn(|| sir_logger::setup(None, [], [], None, "my_app")))
+///     .collect();
+///
+/// let succeeded = handles
+///     .into_iter()
+///     .map(|handle| handle.join().unwrap())
+///     .filter(Result::is_ok)
+///     .count();
+///
+/// assert_eq!(succeeded, 1);
+/// ```
+pub fn setup(
     level_override: Option<log::LevelFilter>,
-    suppress: [&'static str; S],
-    high_priority: [&'static str; H],
+    suppress: impl IntoIterator<Item = &'static str>,
+    high_priority: impl IntoIterator<Item = &'static str>,
     log_file: Option<&dyn AsRef<Path>>,
     root: &'static str,
-) {
-    // This was not in the original, but you can never be *too* safe.
-    if PREVENT_MULTI_INIT.get().is_some() {
-        log::warn!("Attempted to initialize logger twice, ensure you call `setup` once.");
-        return;
+) -> Result<(), SetupError> {
+    let mut builder = LoggerBuilder::new()
+        .suppress(suppress)
+        .high_priority(high_priority)
+        .root(root);
+
+    if let Some(level) = level_override {
+        builder = builder.level(level);
     }
 
-    // Check if log level is overridden, if not, attempt to look
-    // for the environment variable and fallback to `Info`
-    let level = level_override.unwrap_or(
-        std::env::var("RUST_LOG")
-            .ok()
-            .and_then(|f| f.to_uppercase().parse::<LevelFilter>().ok())
-            .unwrap_or(LevelFilter::Info),
-    );
-
-    // Setup the colors of each level, this'll only be used when
-    // printing the name of the log level e.g. "INFO".
-    let colors_level = ColoredLevelConfig::new()
-        .error(Color::Red)
-        .warn(Color::Yellow)
-        .info(Color::Green)
-        .debug(Color::White)
-        .trace(Color::BrightBlack);
-
-    // Declare the main logging module
-    let mut dispatch = fern::Dispatch::new()
-        // Tell fern how to format logs nicely.
-        .format(move |out, message, record| {
-            out.finish(format_args!(
-                "[\x1B[34m{date}\x1B[0m {color_line}{level}\x1B[0m \x1B[32m{target}\x1B[0m] {message}",
-                color_line = format_args!(
-                    "\x1B[{}m",
-                    colors_level.get_color(&record.level()).to_fg_str()
-                ),
-                date = humantime::format_rfc3339_seconds(SystemTime::now()),
-                target = record.target(),
-                level = colors_level.color(record.level()),
-                message = message,
-            ));
-        })
-        // Setup the default logging levels for all crates.
-        .level(match level {
-            log::LevelFilter::Trace => {
-                log::LevelFilter::Trace
-            }
-            log::LevelFilter::Error => {
-                log::LevelFilter::Error
-            }
-            log::LevelFilter::Off => {
-                log::LevelFilter::Off
-            }
-            _ => {
-                log::LevelFilter::Warn
-            }
-        })
-        // Override the main crate to have different
-        // log levels.
-        .level_for(root, level)
+    if let Some(log_file) = log_file {
+        builder = builder.log_file(log_file.as_ref());
+    }
 
-        // Ensure that stdout gets logging info
-        .chain(std::io::stdout());
+    builder.init()
+}
+
+/// Check everything that could make [`setup`](fn@setup) fail, without
+/// installing the logger, see [`LoggerBuilder::validate`].
+///
+/// ## Example
+///
+/// ```rust
+/// let result = sir_logger::validate(None, [""], [], None, "my_app");
+/// assert!(matches!(result, Err(sir_logger::SetupError::EmptyTarget)));
+/// ```
+pub fn validate(
+    level_override: Option<log::LevelFilter>,
+    suppress: impl IntoIterator<Item = &'static str>,
+    high_priority: impl IntoIterator<Item = &'static str>,
+    log_file: Option<&dyn AsRef<Path>>,
+    root: &'static str,
+) -> Result<(), SetupError> {
+    let mut builder = LoggerBuilder::new()
+        .suppress(suppress)
+        .high_priority(high_priority)
+        .root(root);
 
+    if let Some(level) = level_override {
+        builder = builder.level(level);
+    }
 
-    // Apply all the overrides.
-    for pkg in high_priority.into_iter() {
-        dispatch = dispatch.level_for(pkg, level);
+    if let Some(log_file) = log_file {
+        builder = builder.log_file(log_file.as_ref());
     }
 
-    for pkg in suppress.into_iter() {
-        dispatch = dispatch.level_for(pkg, log::LevelFilter::Off);
+    builder.validate()
+}
+
+/// Setup the logger without having to pass the name of your crate, this
+/// expands `env!("CARGO_CRATE_NAME")` where it is called, so it always
+/// gets the right name.
+///
+/// `setup!()` is the same as [`init_default`], and
+/// `setup!(level_override, suppress, high_priority, log_file)` is the same
+/// as [`setup`](fn@setup) with the crate name as the `root`.
+///
+/// The functions are still useful if the root isn't the crate that calls
+/// them, e.g. when the logger is setup by a shared crate in a workspace.
+/// `CARGO_CRATE_NAME` also uses `_` instead of `-`, just like the log
+/// targets, so `my-app` becomes `my_app`. Passing `env!("CARGO_PKG_NAME")`
+/// to the functions doesn't do that.
+///
+/// ## Example
+///
+/// ```rust
+/// use log::LevelFilter;
+///
+/// sir_logger::setup!(Some(LevelFilter::Debug), ["very_verbose_crate"], [], None)
+///     .expect("Unable to setup logger");
+/// ```
+#[macro_export]
+macro_rules! setup {
+    () => {
+        $crate::init_default(env!("CARGO_CRATE_NAME"))
+    };
+    ($level_override:expr, $suppress:expr, $high_priority:expr, $log_file:expr $(,)?) => {
+        $crate::setup(
+            $level_override,
+            $suppress,
+            $high_priority,
+            $log_file,
+            env!("CARGO_CRATE_NAME"),
+        )
+    };
+}
+
+/// Setup the logger with the defaults, this logs at `INFO` (or whatever
+/// `RUST_LOG` says) to stdout, with colors when stdout is a terminal.
+///
+/// This is the same as `setup(None, [], [], None, root)`. The library can't
+/// find the name of your crate by itself, `env!` would give the name of
+/// `sir_logger` instead, so pass `env!("CARGO_PKG_NAME")` as the `root`,
+/// or use [`setup!()`](crate::setup!) which does that for you.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::init_default(env!("CARGO_PKG_NAME")).expect("Unable to setup logger");
+///
+/// log::info!("hello");
+/// ```
+pub fn init_default(root: &'static str) -> Result<(), SetupError> {
+    setup(None, [], [], None, root)
+}
+
+/// Setup the logger for a `--quiet` flag, only errors are logged and
+/// `RUST_LOG` is ignored. Everything else is the same as [`init_default`].
+///
+/// Panics are logged as errors, so they still show up. For no logs at all
+/// use [`setup`](fn@setup) with `Some(LevelFilter::Off)`, panics are then
+/// left to the panic hook that was there before, which prints them to stderr.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::init_quiet(env!("CARGO_PKG_NAME")).expect("Unable to setup logger");
+///
+/// log::warn!("not shown");
+/// log::error!("shown");
+///
+/// let counts = sir_logger::level_counts();
+/// assert_eq!((counts.warn, counts.error), (0, 1));
+/// ```
+pub fn init_quiet(root: &'static str) -> Result<(), SetupError> {
+    setup(Some(log::LevelFilter::Error), [], [], None, root)
+}
+
+/// Setup a logger that throws everything away, e.g. for benchmarks. Unlike
+/// [`setup`](fn@setup) with `Some(LevelFilter::Off)`, nothing else is setup
+/// either, no `RUST_LOG`, log files or panic hook. The max level is `Off`
+/// for good, even [`set_level`] can't change it, so the `log` macros skip
+/// everything straight away.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::init_noop().expect("Unable to setup logger");
+///
+/// assert!(!log::log_enabled!(log::Level::Error));
+/// sir_logger::set_level(log::LevelFilter::Trace);
+/// assert!(!log::log_enabled!(log::Level::Error));
+/// ```
+pub fn init_noop() -> Result<(), SetupError> {
+    if PREVENT_MULTI_INIT
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return Err(SetupError::AlreadyInitialized);
     }
 
-    // If the log file is be set, use it.
-    if let Some(log_file) = log_file {
-        dispatch = dispatch.chain(fern::log_file(log_file).unwrap());
+    if let Err(err) = switch::register() {
+        PREVENT_MULTI_INIT.store(false, Ordering::Release);
+        return Err(err);
     }
 
-    // Apply all the logging info
-    dispatch.apply().unwrap();
-
-    // Set a nicer looking panic hook, so incase there ever is a panic, it'll
-    // be handled nicer.
-    #[cfg(not(feature = "no-panic-handler"))]
-    panic::set_hook(Box::new(|info| {
-        // Print debug info and where the panic happened.
-        if let Some(location) = info.location() {
-            debug!(
-                "panic occurred in file '{}:{}'",
-                location.file(),
-                location.line()
-            );
-        }
+    let mut filters = filter::Filters::new(log::LevelFilter::Off, None);
+    filters.cap(log::LevelFilter::Off);
+    filter::install(filters);
+
+    switch::install(log::LevelFilter::Off, Box::new(switch::Noop));
+    Ok(())
+}
 
-        // Try to downcast the panic error object into a `&str` or `String`,
-        // if this fails, just debug-print the error.
-        let msg = match info.payload().downcast_ref::<&'static str>() {
-            Some(s) => *s,
-            None => match info.payload().downcast_ref::<String>() {
-                Some(s) => &s[..],
-                None => &format!("{:?}", info.payload()),
-            },
-        };
-
-        error!("{msg}");
-
-        // Exit with a failure error code
-        std::process::exit(1);
-    }));
-
-    // This was not in the original, but you can never be *too* safe.
-    PREVENT_MULTI_INIT
-        .set(())
-        .expect("Unable to set initialized flag");
+/// Whether the logger has been setup (and not reset since), e.g. so a
+/// library can setup logging only when the program hasn't already.
+///
+/// ## Example
+///
+/// ```rust
+/// if !sir_logger::is_initialized() {
+///     sir_logger::init_default("my_lib").expect("Unable to setup logger");
+/// }
+///
+/// assert!(sir_logger::is_initialized());
+/// ```
+pub fn is_initialized() -> bool {
+    PREVENT_MULTI_INIT.load(Ordering::Acquire)
+}
+
+/// Flush every output of the logger, e.g. the log files.
+///
+/// Call this before exiting to make sure nothing is lost, the panic hook
+/// already does this for you.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::setup(None, [], [], None, "my_app").expect("Unable to setup logger");
+///
+/// log::info!("shutting down");
+/// sir_logger::flush();
+/// ```
+pub fn flush() {
+    log::logger().flush();
 }
@@ -0,0 +1,80 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// One counter per level, from `Error` to `Trace`.
+static COUNTS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+/// How many records have been logged at each level, see [`level_counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LevelCounts {
+    pub error: u64,
+    pub warn: u64,
+    pub info: u64,
+    pub debug: u64,
+    pub trace: u64,
+}
+
+impl LevelCounts {
+    /// All the records together.
+    pub fn total(&self) -> u64 {
+        self.error + self.warn + self.info + self.debug + self.trace
+    }
+}
+
+/// Create the output that counts the records.
+pub(crate) fn output() -> fern::Output {
+    fern::Output::call(|record| {
+        COUNTS[record.level() as usize - 1].fetch_add(1, Ordering::Relaxed);
+    })
+}
+
+/// How many records have been logged at each level since the program
+/// started, e.g. for a health check that reports the number of errors.
+///
+/// Only the records that got past the level filters (and
+/// [`filter`](crate::LoggerBuilder::filter)) are counted. When
+/// [`dedup`](crate::LoggerBuilder::dedup) is on the repeats it drops aren't
+/// counted, each `... (repeated N times)` line counts as a single record.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::LoggerBuilder::new()
+///     .root("my_app")
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// log::error!("oh no");
+/// assert_eq!(sir_logger::level_counts().error, 1);
+/// ```
+pub fn level_counts() -> LevelCounts {
+    let count = |index: usize| COUNTS[index].load(Ordering::Relaxed);
+
+    LevelCounts {
+        error: count(0),
+        warn: count(1),
+        info: count(2),
+        debug: count(3),
+        trace: count(4),
+    }
+}
@@ -0,0 +1,148 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// How long to wait on a slow collector before giving up on a line.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long to wait after a failed connection before trying again, the
+/// lines in between are dropped.
+const RETRY: Duration = Duration::from_secs(5);
+
+/// How the lines are sent to a remote collector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Send each line over a single TCP connection, which is reconnected
+    /// if it breaks.
+    Tcp,
+    /// Send each line as its own UDP datagram.
+    Udp,
+}
+
+enum Connection {
+    Tcp(Option<TcpStream>),
+    Udp(UdpSocket),
+}
+
+/// Sends every line to a remote collector.
+///
+/// Like the rotating files, a record is held back until `flush`, so it is
+/// always sent in one piece. There's no buffering past that, a line is
+/// dropped if it can't be sent within a second, so a dead collector can
+/// never fill up memory or stall the program for long. Once the collector
+/// can't be reached it isn't tried again for a few seconds, so every line
+/// doesn't have to wait for it.
+pub(crate) struct NetworkWriter {
+    addr: SocketAddr,
+    connection: Connection,
+    /// When reconnecting last failed, so it isn't tried again straight away.
+    failed: Option<Instant>,
+    gelf: bool,
+    pending: Vec<u8>,
+}
+
+impl NetworkWriter {
+    /// Connect to `addr`, this fails straight away if the collector
+    /// can't be reached over TCP. GELF messages aren't ended with a newline.
+    pub(crate) fn connect(addr: SocketAddr, transport: Transport, gelf: bool) -> io::Result<Self> {
+        let connection = match transport {
+            Transport::Tcp => Connection::Tcp(Some(tcp(addr)?)),
+            Transport::Udp => {
+                let local: SocketAddr = if addr.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Connection::Udp(socket)
+            }
+        };
+
+        Ok(Self {
+            addr,
+            connection,
+            failed: None,
+            gelf,
+            pending: Vec::new(),
+        })
+    }
+}
+
+fn tcp(addr: SocketAddr) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    Ok(stream)
+}
+
+impl Write for NetworkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut line = std::mem::take(&mut self.pending);
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        // GELF uses a datagram per message over UDP, and a nul byte
+        // between messages over TCP.
+        if self.gelf {
+            while line.last().is_some_and(|byte| *byte == b'\n' || *byte == b'\r') {
+                line.pop();
+            }
+
+            if let Connection::Tcp(_) = self.connection {
+                line.push(0);
+            }
+        }
+
+        // Failures are ignored so a missing collector doesn't
+        // fill stderr up with errors.
+        match &mut self.connection {
+            Connection::Udp(socket) => {
+                let _ = socket.send(&line);
+            }
+            Connection::Tcp(stream) => {
+                let waiting = self.failed.is_some_and(|failed| failed.elapsed() < RETRY);
+                if stream.is_none() && !waiting {
+                    *stream = tcp(self.addr).ok();
+                    self.failed = stream.is_none().then(Instant::now);
+                }
+
+                if let Some(connected) = stream {
+                    if connected.write_all(&line).is_err() {
+                        // Try again with a fresh connection on the next line.
+                        *stream = None;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
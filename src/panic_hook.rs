@@ -0,0 +1,187 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::context;
+use log::{log, Level};
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic::{self, PanicHookInfo};
+use std::sync::OnceLock;
+
+/// Panics are logged as coming from the crate itself, rather
+/// than this module.
+const TARGET: &str = "sir_logger";
+
+/// How the panic hook should behave.
+#[derive(Debug, Clone)]
+pub(crate) struct PanicOptions {
+    /// Exit the process after logging instead of unwinding.
+    pub(crate) exit: bool,
+    /// The exit code to use when exiting.
+    pub(crate) exit_code: i32,
+    /// Run the hook that was installed before ours, after logging.
+    pub(crate) chain: bool,
+    /// Log the panic as a single record with `panic.*` fields.
+    pub(crate) structured: bool,
+    /// Log everything at this level, instead of the location at `debug`
+    /// and the rest at `error`.
+    pub(crate) level: Option<Level>,
+    /// Where to POST the panic to, if anywhere.
+    #[cfg(feature = "panic-webhook")]
+    pub(crate) webhook: Option<String>,
+}
+
+/// A panic hook, as returned by `panic::take_hook`.
+type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// The hook that was there before ours was first installed. Later installs
+/// replace our own hook, so this has to be kept from the first one.
+static PREVIOUS: OnceLock<Hook> = OnceLock::new();
+
+/// Set a nicer looking panic hook that sends panics through the logger.
+pub(crate) fn install(options: PanicOptions) {
+    let previous = PREVIOUS.get_or_init(panic::take_hook);
+
+    panic::set_hook(Box::new(move |info| {
+        // Try to downcast the panic error object into a `&str` or `String`,
+        // if this fails, just debug-print the error.
+        let msg = match info.payload().downcast_ref::<&'static str>() {
+            Some(s) => *s,
+            None => match info.payload().downcast_ref::<String>() {
+                Some(s) => &s[..],
+                None => &format!("{:?}", info.payload()),
+            },
+        };
+
+        // This only captures anything if `RUST_BACKTRACE` (or
+        // `RUST_LIB_BACKTRACE`) is set, so normal runs stay quiet.
+        let backtrace = Backtrace::capture();
+
+        let level = options.level.unwrap_or(Level::Error);
+        if options.structured {
+            log_structured(level, msg, info, &backtrace);
+        } else {
+            // Print debug info and where the panic happened.
+            if let Some(location) = info.location() {
+                log!(
+                    target: TARGET,
+                    options.level.unwrap_or(Level::Debug),
+                    "panic occurred in file '{}:{}'",
+                    location.file(),
+                    location.line()
+                );
+            }
+
+            log!(target: TARGET, level, "{msg}");
+
+            if backtrace.status() == BacktraceStatus::Captured {
+                log!(target: TARGET, level, "stack backtrace:\n{backtrace}");
+            }
+        }
+
+        #[cfg(feature = "panic-webhook")]
+        if let Some(url) = &options.webhook {
+            let backtrace = (backtrace.status() == BacktraceStatus::Captured).then_some(&backtrace);
+            if let Err(err) = post_webhook(url, msg, info.location(), backtrace) {
+                log::warn!(target: TARGET, "Unable to send the panic to the webhook: {err}");
+            }
+        }
+
+        // Make sure the panic actually makes it into the log files,
+        // especially if the process is about to exit.
+        log::logger().flush();
+
+        // When the level is turned off the panic would disappear without
+        // a trace, so leave it to the old hook instead.
+        let logged = log::log_enabled!(target: TARGET, level);
+        if options.chain || !logged {
+            previous(info);
+        }
+
+        // Exit with a failure error code, otherwise let the panic
+        // carry on unwinding. There's no process to exit in the browser.
+        if options.exit && !cfg!(all(target_arch = "wasm32", feature = "wasm")) {
+            std::process::exit(options.exit_code);
+        }
+    }));
+}
+
+/// Log the panic as one record, with everything else in `panic.*` context
+/// fields so the JSON and logfmt layouts give each its own key.
+fn log_structured(level: Level, msg: &str, info: &PanicHookInfo<'_>, backtrace: &Backtrace) {
+    let mut fields = vec![context::with_context("panic.message", msg)];
+
+    if let Some(location) = info.location() {
+        fields.push(context::with_context("panic.file", location.file()));
+        fields.push(context::with_context("panic.line", location.line()));
+    }
+
+    if backtrace.status() == BacktraceStatus::Captured {
+        fields.push(context::with_context("panic.backtrace", backtrace));
+    }
+
+    log!(target: TARGET, level, "{msg}");
+}
+
+/// Send the panic to `url` as a JSON object. The `text` field is
+/// there so it shows up as a message in Slack.
+#[cfg(feature = "panic-webhook")]
+fn post_webhook(
+    url: &str,
+    msg: &str,
+    location: Option<&panic::Location>,
+    backtrace: Option<&Backtrace>,
+) -> Result<(), Box<ureq::Error>> {
+    use crate::format::json_string;
+    use std::time::Duration;
+
+    let mut body = String::from("{\"text\":");
+    match location {
+        Some(location) => json_string(
+            &mut body,
+            format_args!("panic at {}:{}: {msg}", location.file(), location.line()),
+        ),
+        None => json_string(&mut body, format_args!("panic: {msg}")),
+    }
+
+    body.push_str(",\"message\":");
+    json_string(&mut body, msg);
+
+    if let Some(location) = location {
+        body.push_str(",\"file\":");
+        json_string(&mut body, location.file());
+        body.push_str(&format!(",\"line\":{}", location.line()));
+    }
+
+    if let Some(backtrace) = backtrace {
+        body.push_str(",\"backtrace\":");
+        json_string(&mut body, backtrace);
+    }
+
+    body.push('}');
+
+    // Don't keep a crashing program around for too long.
+    ureq::post(url)
+        .timeout(Duration::from_secs(5))
+        .set("Content-Type", "application/json")
+        .send_string(&body)?;
+
+    Ok(())
+}
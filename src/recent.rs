@@ -0,0 +1,81 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// The most recent log lines, oldest first.
+struct Recent {
+    capacity: usize,
+    lines: VecDeque<String>,
+}
+
+static RECENT: Mutex<Recent> = Mutex::new(Recent {
+    capacity: 0,
+    lines: VecDeque::new(),
+});
+
+/// Start keeping the last `capacity` lines, throwing away anything
+/// kept so far.
+pub(crate) fn start(capacity: usize) {
+    let mut recent = RECENT.lock().unwrap_or_else(|err| err.into_inner());
+    recent.capacity = capacity;
+    recent.lines = VecDeque::with_capacity(capacity);
+}
+
+/// Create the output that feeds the ring buffer.
+pub(crate) fn output() -> fern::Output {
+    fern::Output::call(|record| {
+        let mut recent = RECENT.lock().unwrap_or_else(|err| err.into_inner());
+        if recent.capacity == 0 {
+            return;
+        }
+
+        // Evict the oldest line to make room.
+        if recent.lines.len() >= recent.capacity {
+            recent.lines.pop_front();
+        }
+
+        recent.lines.push_back(record.args().to_string());
+    })
+}
+
+/// The most recent log lines, oldest first, without any coloring.
+///
+/// This is always empty unless [`LoggerBuilder::ring_buffer`](crate::LoggerBuilder::ring_buffer)
+/// was used, e.g. for an admin endpoint that shows the last 100 lines.
+///
+/// ## Example
+///
+/// ```rust
+/// sir_logger::LoggerBuilder::new()
+///     .root("my_app")
+///     .ring_buffer(100)
+///     .init()
+///     .expect("Unable to setup logger");
+///
+/// log::info!("hello");
+/// assert!(sir_logger::recent_logs()[0].ends_with("hello"));
+/// ```
+pub fn recent_logs() -> Vec<String> {
+    let recent = RECENT.lock().unwrap_or_else(|err| err.into_inner());
+    recent.lines.iter().cloned().collect()
+}
@@ -0,0 +1,130 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use log::Level;
+use std::fmt::{self, Write as _};
+use std::sync::Once;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+
+/// A `tracing` subscriber that hands every event to the logger, so they
+/// are filtered, formatted and written just like `log` records. Spans
+/// aren't shown, only the events inside them.
+struct LogSubscriber;
+
+impl Subscriber for LogSubscriber {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        // The levels can be changed at any time, so every event has to be
+        // checked again when it happens.
+        if metadata.is_span() {
+            Interest::never()
+        } else {
+            Interest::sometimes()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let metadata = log::Metadata::builder()
+            .level(level(metadata))
+            .target(metadata.target())
+            .build();
+
+        log::logger().enabled(&metadata)
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        // Never called, as spans are never enabled.
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let metadata = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        log::logger().log(
+            &log::Record::builder()
+                .level(level(metadata))
+                .target(metadata.target())
+                .args(format_args!("{}{}", fields.message, fields.rest))
+                .module_path(metadata.module_path())
+                .file(metadata.file())
+                .line(metadata.line())
+                .build(),
+        );
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// The `log` level of a `tracing` event.
+fn level(metadata: &Metadata<'_>) -> Level {
+    match *metadata.level() {
+        tracing::Level::ERROR => Level::Error,
+        tracing::Level::WARN => Level::Warn,
+        tracing::Level::INFO => Level::Info,
+        tracing::Level::DEBUG => Level::Debug,
+        tracing::Level::TRACE => Level::Trace,
+    }
+}
+
+/// The fields of an event, the `message` and then everything else
+/// as `key=value`, like the context fields.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        // Writing to a `String` can't fail.
+        let _ = if field.name() == "message" {
+            write!(self.message, "{value:?}")
+        } else {
+            write!(self.rest, " {}={value:?}", field.name())
+        };
+    }
+}
+
+/// Send `tracing` events to the logger, the first time the logger is
+/// setup. The subscriber can't be replaced, but it always logs to whichever
+/// logger is setup at the time. Programs that already have their own
+/// subscriber keep it.
+pub(crate) fn install() {
+    static INSTALLED: Once = Once::new();
+
+    INSTALLED.call_once(|| {
+        let _ = tracing::subscriber::set_global_default(LogSubscriber);
+    });
+}
@@ -0,0 +1,148 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use crate::context;
+use crate::file;
+use crate::filter::{self, Filters};
+use crate::recent;
+use crate::{SetupError, PREVENT_MULTI_INIT};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{RwLock, RwLockReadGuard};
+
+/// The logger handed to the `log` crate. It can only ever be installed
+/// once, so all it does is pass records on to whichever fern dispatch is
+/// currently switched in.
+pub(crate) struct Switch {
+    inner: RwLock<Option<Box<dyn Log>>>,
+}
+
+pub(crate) static LOGGER: Switch = Switch {
+    inner: RwLock::new(None),
+};
+
+/// Whether `LOGGER` has been handed to the `log` crate yet.
+static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+impl Switch {
+    fn read(&self) -> RwLockReadGuard<'_, Option<Box<dyn Log>>> {
+        // A panic while logging shouldn't stop everything else from logging.
+        self.inner.read().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Log for Switch {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.read()
+            .as_ref()
+            .is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(logger) = self.read().as_ref() {
+            logger.log(record);
+        }
+
+        // Only now that the lock on every output has been let go.
+        file::report_deferred();
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = self.read().as_ref() {
+            logger.flush();
+        }
+
+        file::flush_buffers();
+    }
+}
+
+/// A logger that throws everything away, see [`init_noop`](crate::init_noop).
+pub(crate) struct Noop;
+
+impl Log for Noop {
+    fn enabled(&self, _: &Metadata) -> bool {
+        false
+    }
+
+    fn log(&self, _: &Record) {}
+
+    fn flush(&self) {}
+}
+
+/// Register `LOGGER` with the `log` crate if this is the first time. This
+/// is the only part of installing that can fail, so it is done before any
+/// of the shared state is changed.
+pub(crate) fn register() -> Result<(), SetupError> {
+    if !REGISTERED.load(Ordering::Acquire) {
+        log::set_logger(&LOGGER)?;
+        REGISTERED.store(true, Ordering::Release);
+    }
+
+    Ok(())
+}
+
+/// Switch in a new logger, [`register`] has to have been called first.
+pub(crate) fn install(level: LevelFilter, logger: Box<dyn Log>) {
+    *LOGGER.inner.write().unwrap_or_else(|err| err.into_inner()) = Some(logger);
+
+    // Anything above the static level was compiled out anyway.
+    log::set_max_level(level.min(log::STATIC_MAX_LEVEL));
+}
+
+/// Flush and remove the current logger, so that the logger
+/// can be setup again.
+pub(crate) fn reset() {
+    log::set_max_level(LevelFilter::Off);
+
+    let old = LOGGER.inner.write().unwrap_or_else(|err| err.into_inner()).take();
+    // The buffers have to be written out before the logger is dropped,
+    // or the rotating files would lose their last lines.
+    if let Some(logger) = old {
+        logger.flush();
+        file::flush_buffers();
+    }
+
+    filter::install(Filters::new(LevelFilter::Off, None));
+    // Nothing from this logger should show up in the next one.
+    recent::start(0);
+    context::clear();
+    PREVENT_MULTI_INIT.store(false, Ordering::Release);
+}
+
+/// Resets the logger when dropped, see
+/// [`LoggerBuilder::init_scoped`](crate::LoggerBuilder::init_scoped).
+#[derive(Debug)]
+#[must_use = "the logger is reset as soon as the guard is dropped"]
+pub struct LoggerGuard {
+    _private: (),
+}
+
+impl LoggerGuard {
+    pub(crate) fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        reset();
+    }
+}
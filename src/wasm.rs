@@ -0,0 +1,49 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+
+use std::time::{Duration, SystemTime};
+use wasm_bindgen::JsValue;
+use web_sys::console;
+
+/// The current time from the browser, `SystemTime::now` panics
+/// on `wasm32-unknown-unknown`.
+pub(crate) fn now() -> SystemTime {
+    let millis = js_sys::Date::now();
+    SystemTime::UNIX_EPOCH + Duration::from_secs_f64(millis / 1000.0)
+}
+
+/// Create the output that writes each line to the browser console, using
+/// the console method that matches the level so they can be filtered in
+/// the dev tools.
+pub(crate) fn console() -> fern::Output {
+    fern::Output::call(|record| {
+        let line = JsValue::from_str(&record.args().to_string());
+
+        match record.level() {
+            log::Level::Error => console::error_1(&line),
+            log::Level::Warn => console::warn_1(&line),
+            log::Level::Info => console::info_1(&line),
+            log::Level::Debug => console::log_1(&line),
+            log::Level::Trace => console::debug_1(&line),
+        }
+    })
+}
@@ -0,0 +1,58 @@
+//
+// `sir_logger` - A simple logging library for rust
+// 
+// Copyright (C) 2025  SirSpudlington
+// 
+// This library is free software; you can redistribute it and/or
+// modify it under the terms of the GNU Lesser General Public
+// License as published by the Free Software Foundation; either
+// version 2.1 of the License, or (at your option) any later version.
+// 
+// This library is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU
+// Lesser General Public License for more details.
+// 
+// You should have received a copy of the GNU Lesser General Public
+// License along with this library; if not, write to the Free Software
+// Foundation, Inc., 51 Franklin Street, Fifth Floor, Boston, MA  02110-1301
+// USA
+
+
+use std::sync::OnceLock;
+use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+use windows_sys::Win32::System::Console::{
+    GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    STD_ERROR_HANDLE, STD_HANDLE, STD_OUTPUT_HANDLE,
+};
+
+/// Turn on escape code support for stdout and stderr, older consoles
+/// like `cmd.exe` print the escape codes as garbage without this.
+/// Returns false if a console refused to turn it on, so colors can be
+/// disabled. Streams that aren't a console are skipped, they never get
+/// colors anyway.
+pub(crate) fn enable_virtual_terminal() -> bool {
+    // The console mode only needs to be set once.
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+
+    *ENABLED.get_or_init(|| enable(STD_OUTPUT_HANDLE) && enable(STD_ERROR_HANDLE))
+}
+
+fn enable(stream: STD_HANDLE) -> bool {
+    // SAFETY: these only read and write the mode of the console, and
+    // every handle is checked before it is used.
+    unsafe {
+        let handle = GetStdHandle(stream);
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return true;
+        }
+
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return true;
+        }
+
+        mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+            || SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
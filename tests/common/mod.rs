@@ -0,0 +1,52 @@
+// The helpers are shared by every test file, but not all of them use everything.
+#![allow(dead_code)]
+
+use sir_logger::{LoggerBuilder, LoggerGuard};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// The logger is global, so the tests in a file take turns with it.
+static LOGGER: Mutex<()> = Mutex::new(());
+
+pub fn lock() -> MutexGuard<'static, ()> {
+    LOGGER.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A new, empty directory that no other test (or run) uses.
+pub fn temp_dir() -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    let name = format!("sir_logger-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+    let dir = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Setup `builder` without stdout, so only `path` gets anything.
+pub fn init(builder: LoggerBuilder, path: &Path) -> LoggerGuard {
+    builder
+        .stdout(false)
+        .log_file(path)
+        .init_scoped()
+        .expect("Unable to setup logger")
+}
+
+/// Run `log` with `builder` writing to a new log file, and return
+/// everything that ended up in the file.
+pub fn log_to_file(builder: LoggerBuilder, log: impl FnOnce()) -> String {
+    log_to_file_at(builder, &temp_dir().join("app.log"), log)
+}
+
+/// [`log_to_file`], with the log file at `path`.
+pub fn log_to_file_at(builder: LoggerBuilder, path: &Path, log: impl FnOnce()) -> String {
+    let _lock = lock();
+
+    let guard = init(builder, path);
+    log();
+    drop(guard);
+
+    fs::read_to_string(path).unwrap()
+}
@@ -0,0 +1,39 @@
+#![cfg(all(unix, feature = "gzip"))]
+
+mod common;
+
+use sir_logger::{LoggerBuilder, Rotation};
+use std::time::{Duration, Instant};
+
+#[test]
+fn failed_compression_is_logged() {
+    let _lock = common::lock();
+    let dir = common::temp_dir();
+
+    // A broken link in the way of `app.log.1.gz`, so compressing it fails.
+    std::os::unix::fs::symlink(dir.join("missing/app.log.1.gz"), dir.join("app.log.1.gz")).unwrap();
+
+    let builder = LoggerBuilder::new()
+        .rotation(Rotation::Size { max_bytes: 10, keep: 3 })
+        .compress(true)
+        .ring_buffer(20);
+    let guard = common::init(builder, &dir.join("app.log"));
+
+    for line in 0..5 {
+        log::info!("line {line}");
+    }
+
+    let started = Instant::now();
+    let warned = loop {
+        let warned = sir_logger::recent_logs().iter().any(|line| line.contains("Unable to compress"));
+        if warned || started.elapsed() > Duration::from_secs(5) {
+            break warned;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    drop(guard);
+
+    assert!(warned);
+    assert!(dir.join("app.log.1").exists());
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "config-file")]
+
+mod common;
+
+use log::{Level, LevelFilter};
+use sir_logger::{LoggerBuilder, SetupError};
+use std::fs;
+
+#[test]
+fn settings_come_from_the_file() {
+    let _lock = common::lock();
+    let path = common::temp_dir().join("logging.toml");
+    fs::write(&path, "level = \"debug\"\nstdout = false\n\n[levels]\nhyper = \"warn\"\n").unwrap();
+
+    let guard = LoggerBuilder::from_file(&path)
+        .expect("Unable to read the logging config")
+        .init_scoped()
+        .expect("Unable to setup logger");
+    let level = sir_logger::current_level();
+    let hyper_info = log::log_enabled!(target: "hyper", Level::Info);
+    drop(guard);
+
+    assert_eq!(level, LevelFilter::Debug);
+    assert!(!hyper_info);
+}
+
+#[test]
+fn unknown_settings_are_an_error() {
+    let path = common::temp_dir().join("logging.toml");
+    fs::write(&path, "levle = \"debug\"\n").unwrap();
+
+    assert!(matches!(LoggerBuilder::from_file(&path), Err(SetupError::Config { .. })));
+}
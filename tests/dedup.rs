@@ -0,0 +1,35 @@
+mod common;
+
+use sir_logger::LoggerBuilder;
+use std::time::{Duration, Instant};
+
+#[test]
+fn summary_is_logged_once_the_window_is_over() {
+    let _lock = common::lock();
+
+    let guard = LoggerBuilder::new()
+        .stdout(false)
+        .dedup(Duration::from_millis(50))
+        .ring_buffer(10)
+        .init_scoped()
+        .expect("Unable to setup logger");
+
+    for _ in 0..4 {
+        log::warn!("disk almost full");
+    }
+
+    // Nothing else is logged, or flushed.
+    let started = Instant::now();
+    let logs = loop {
+        let logs = sir_logger::recent_logs();
+        if logs.len() > 1 || started.elapsed() > Duration::from_secs(5) {
+            break logs;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    drop(guard);
+
+    assert_eq!(logs.len(), 2);
+    assert!(logs[1].ends_with("... (repeated 3 times)"));
+}
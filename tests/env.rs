@@ -0,0 +1,26 @@
+mod common;
+
+use sir_logger::{ColorChoice, LoggerBuilder};
+use std::fs;
+
+#[test]
+fn overrides_fill_in_what_the_builder_left_unset() {
+    let _lock = common::lock();
+    let path = common::temp_dir().join("app.log");
+
+    std::env::set_var("SIR_LOG_FILE", &path);
+    std::env::set_var("SIR_LOG_FORMAT", "json");
+    let guard = LoggerBuilder::new()
+        .color(ColorChoice::Never)
+        .stdout(false)
+        .init_scoped();
+    std::env::remove_var("SIR_LOG_FILE");
+    std::env::remove_var("SIR_LOG_FORMAT");
+
+    let guard = guard.expect("Unable to setup logger");
+    log::info!("configured from the environment");
+    drop(guard);
+
+    let logs = fs::read_to_string(&path).unwrap();
+    assert!(logs.starts_with('{') && logs.contains("configured from the environment"));
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use log::LevelFilter;
+use sir_logger::{FileMode, LogFile, LoggerBuilder, Retention, Rotation};
+use std::fs::{self, File};
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn size_rotation_never_splits_a_record() {
+    let _lock = common::lock();
+    let dir = common::temp_dir();
+
+    let builder = LoggerBuilder::new().rotation(Rotation::Size { max_bytes: 100, keep: 2 });
+    let guard = common::init(builder, &dir.join("log.txt"));
+    log::info!("{}", "first ".repeat(10));
+    log::info!("{}", "second ".repeat(10));
+    drop(guard);
+
+    let old = fs::read_to_string(dir.join("log.txt.1")).unwrap();
+    let new = fs::read_to_string(dir.join("log.txt")).unwrap();
+    assert!(old.contains("first") && !old.contains("second"));
+    assert!(new.contains("second") && !new.contains("first"));
+}
+
+#[test]
+fn missing_directories_are_created() {
+    let path = common::temp_dir().join("nested/logs/app.log");
+
+    let logs = common::log_to_file_at(LoggerBuilder::new(), &path, || log::info!("in a new directory"));
+    assert!(logs.contains("in a new directory"));
+}
+
+#[test]
+fn file_modes() {
+    let path = common::temp_dir().join("app.log");
+    let run = |mode, line| {
+        common::log_to_file_at(LoggerBuilder::new().file_mode(mode), &path, || log::info!("{line}"))
+    };
+
+    run(FileMode::Append, "run 1");
+    let appended = run(FileMode::Append, "run 2");
+    assert!(appended.contains("run 1") && appended.contains("run 2"));
+
+    let truncated = run(FileMode::Truncate, "run 3");
+    assert!(!truncated.contains("run 2") && truncated.contains("run 3"));
+}
+
+#[test]
+fn buffered_lines_are_written_on_flush() {
+    let _lock = common::lock();
+    let path = common::temp_dir().join("app.log");
+
+    let _guard = common::init(LoggerBuilder::new().file_buffer(64 * 1024), &path);
+    for line in 0..100 {
+        log::info!("line {line}");
+    }
+
+    // Nothing has been written yet, then all 100 lines in one go.
+    assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    sir_logger::flush();
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 100);
+}
+
+#[test]
+fn files_with_their_own_level() {
+    let dir = common::temp_dir();
+    let errors = dir.join("errors.log");
+
+    let builder = LoggerBuilder::new().add_log_file(LogFile::new(&errors).level(LevelFilter::Error));
+    let all = common::log_to_file_at(builder, &dir.join("app.log"), || {
+        log::info!("starting up");
+        log::error!("out of disk space");
+    });
+
+    let errors = fs::read_to_string(errors).unwrap();
+    assert_eq!(all.lines().count(), 2);
+    assert!(errors.contains("out of disk space") && !errors.contains("starting up"));
+}
+
+#[test]
+fn file_level_is_separate_from_stdout() {
+    let builder = LoggerBuilder::new()
+        .level(LevelFilter::Debug)
+        .stdout_level(LevelFilter::Info)
+        .file_level(LevelFilter::Debug);
+
+    let logs = common::log_to_file(builder, || {
+        log::trace!("nowhere");
+        log::debug!("just in the file");
+        log::info!("everywhere");
+    });
+
+    assert!(logs.contains("just in the file") && logs.contains("everywhere"));
+    assert!(!logs.contains("nowhere"));
+}
+
+#[test]
+fn retention_only_deletes_rotated_files() {
+    let _lock = common::lock();
+    let dir = common::temp_dir();
+
+    let rotations = ["app-2020-09-13.txt", "app-2020-09-12-07.txt.gz"];
+    let unrelated = ["app-errors.txt", "app-errors-2020-09-13.txt", "app-notes.txt", "other-2020-09-13.txt"];
+
+    // Everything was last written years ago.
+    let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    for name in rotations.iter().chain(&unrelated) {
+        File::create(dir.join(name)).unwrap().set_modified(old).unwrap();
+    }
+
+    let builder = LoggerBuilder::new().rotation(Rotation::Daily).retention(Retention {
+        max_age: Some(Duration::from_secs(24 * 60 * 60)),
+        max_total_bytes: None,
+    });
+    drop(common::init(builder, &dir.join("app.txt")));
+
+    assert!(rotations.iter().all(|name| !dir.join(name).exists()));
+    assert!(unrelated.iter().all(|name| dir.join(name).exists()));
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use log::LevelFilter;
+use sir_logger::{LevelPadding, LoggerBuilder, Timestamp};
+
+#[test]
+fn padded_levels_line_up() {
+    let _lock = common::lock();
+
+    for padding in [LevelPadding::Left, LevelPadding::Right] {
+        let guard = LoggerBuilder::new()
+            .stdout(false)
+            .level(LevelFilter::Trace)
+            .timestamp(Timestamp::UnixSeconds)
+            .level_padding(padding)
+            .ring_buffer(5)
+            .init_scoped()
+            .expect("Unable to setup logger");
+
+        log::error!("message");
+        log::warn!("message");
+        log::info!("message");
+        log::debug!("message");
+        log::trace!("message");
+        let logs = sir_logger::recent_logs();
+        drop(guard);
+
+        let offsets: Vec<usize> = logs.iter().map(|line| line.find("message").unwrap()).collect();
+        assert_eq!(offsets.len(), 5);
+        assert!(offsets.iter().all(|offset| *offset == offsets[0]));
+    }
+}
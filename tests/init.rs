@@ -0,0 +1,58 @@
+mod common;
+
+use sir_logger::LoggerBuilder;
+
+#[test]
+fn nothing_is_kept_between_inits() {
+    let _lock = common::lock();
+
+    let guard = LoggerBuilder::new()
+        .stdout(false)
+        .ring_buffer(10)
+        .init_scoped()
+        .expect("Unable to setup logger");
+    sir_logger::set_context("request", 1);
+    log::info!("first");
+    assert_eq!(sir_logger::recent_logs().len(), 1);
+    drop(guard);
+
+    let guard = LoggerBuilder::new()
+        .stdout(false)
+        .ring_buffer(10)
+        .init_scoped()
+        .expect("Unable to setup logger");
+    assert!(sir_logger::recent_logs().is_empty());
+
+    log::info!("second");
+    let logs = sir_logger::recent_logs();
+    drop(guard);
+
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].ends_with("second"));
+}
+
+#[test]
+fn failed_init_changes_nothing() {
+    let _lock = common::lock();
+    let dir = common::temp_dir();
+
+    // A directory isn't a log file.
+    let result = LoggerBuilder::new()
+        .stdout(false)
+        .ring_buffer(10)
+        .context("request", 7)
+        .log_file(&dir)
+        .init();
+    assert!(result.is_err());
+
+    let guard = LoggerBuilder::new()
+        .stdout(false)
+        .ring_buffer(10)
+        .init_scoped()
+        .expect("Unable to setup logger");
+    log::info!("second");
+    let logs = sir_logger::recent_logs();
+    drop(guard);
+
+    assert!(!logs[0].contains("request"));
+}
@@ -0,0 +1,19 @@
+#![cfg(all(feature = "panic-hook", not(feature = "no-panic-handler")))]
+
+mod common;
+
+use sir_logger::LoggerBuilder;
+use std::fs;
+
+#[test]
+fn panics_flush_buffered_files() {
+    let _lock = common::lock();
+    let path = common::temp_dir().join("app.log");
+
+    let _guard = common::init(LoggerBuilder::new().file_buffer(4096), &path);
+    let result = std::thread::spawn(|| panic!("worker failed")).join();
+    assert!(result.is_err());
+
+    // The hook flushed the buffered file, without waiting for the guard.
+    assert!(fs::read_to_string(&path).unwrap().contains("worker failed"));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use log::LevelFilter;
+use sir_logger::LoggerBuilder;
+
+#[test]
+fn events_are_logged_with_their_fields() {
+    let logs = common::log_to_file(LoggerBuilder::new().level(LevelFilter::Info), || {
+        tracing::info!(user = "ferris", attempts = 3, "logged in");
+        tracing::debug!("too verbose");
+    });
+
+    assert_eq!(logs.lines().count(), 1);
+    assert!(logs.trim_end().ends_with("] logged in user=ferris attempts=3"));
+}
+
+#[test]
+fn events_use_the_crate_levels() {
+    let builder = LoggerBuilder::new()
+        .level(LevelFilter::Debug)
+        .external_level(LevelFilter::Warn)
+        .high_priority(["my_lib"])
+        .suppress(["noisy"]);
+
+    let logs = common::log_to_file(builder, || {
+        tracing::debug!(target: "my_lib::db", "high priority");
+        tracing::info!(target: "other", "external");
+        tracing::error!(target: "noisy", "suppressed");
+    });
+
+    assert!(logs.contains("high priority"));
+    assert!(!logs.contains("external") && !logs.contains("suppressed"));
+}
+
+#[test]
+fn levels_changed_at_runtime_apply_to_events() {
+    let logs = common::log_to_file(LoggerBuilder::new().level(LevelFilter::Info), || {
+        tracing::debug!("before");
+        sir_logger::set_level(LevelFilter::Debug);
+        tracing::debug!("after");
+    });
+
+    assert!(!logs.contains("before") && logs.contains("after"));
+}
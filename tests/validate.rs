@@ -0,0 +1,34 @@
+mod common;
+
+use sir_logger::{LoggerBuilder, SetupError};
+use std::fs;
+
+#[test]
+fn nothing_is_left_behind() {
+    let dir = common::temp_dir();
+
+    LoggerBuilder::new()
+        .log_file(dir.join("logs/app.log"))
+        .validate()
+        .expect("The log file should be openable");
+
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+#[cfg(unix)]
+fn read_only_directories_are_an_error() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = common::temp_dir();
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o555)).unwrap();
+
+    // Root can write to it anyway.
+    let writable = fs::File::create(dir.join("probe")).is_ok();
+    let result = LoggerBuilder::new().log_file(dir.join("app.log")).validate();
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+    if !writable {
+        assert!(matches!(result, Err(SetupError::LogFile { .. })));
+    }
+}